@@ -1,4 +1,10 @@
+use crate::epiclist::EpicList;
+use crate::equivalents::Equivalents;
 use crate::error_template::{AppError, ErrorTemplate};
+use crate::feed::{Feed, Follows};
+use crate::lists::{ListPage, Lists};
+use crate::projects::ProjectPage;
+use crate::search::Search;
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
@@ -7,6 +13,8 @@ use leptos_router::*;
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
+    // loaded once for all follow buttons on a page
+    Follows::provide();
 
     view! {
 
@@ -16,9 +24,8 @@ pub fn App() -> impl IntoView {
         <Stylesheet id="leptos" href="/pkg/app.css"/>
 
         // sets the document title
-        <Title text="Welcome to Leptos"/>
+        <Title formatter=|text| format!("{text} | EpicList")/>
 
-        // content for this welcome page
         <Router fallback=|| {
             let mut outside_errors = Errors::default();
             outside_errors.insert_with_default_key(AppError::NotFound);
@@ -27,39 +34,33 @@ pub fn App() -> impl IntoView {
             }
             .into_view()
         }>
-            <main>
-                <Routes>
-                    <Route path="" view=HomePage/>
-                </Routes>
-            </main>
+            <div class="flex flex-col min-h-screen font-mono text-white bg-gradient-to-tl from-gray-900 to-gray-700">
+                <Nav/>
+                <main class="flex flex-col gap-6 p-8">
+                    <Routes>
+                        <Route path="" view=EpicList/>
+                        <Route path="/lists" view=Lists/>
+                        <Route path="/lists/:owner/:repo" view=ListPage ssr=SsrMode::Async/>
+                        <Route path="/search" view=Search/>
+                        <Route path="/feed" view=Feed/>
+                        <Route path="/projects/:forge/:owner/:repo" view=ProjectPage ssr=SsrMode::Async/>
+                        <Route path="/equivalents/:owner/:repo" view=Equivalents/>
+                    </Routes>
+                </main>
+            </div>
         </Router>
     }
 }
 
-/// Renders the home page of your application.
+/// The header shown above every page.
 #[component]
-fn HomePage() -> impl IntoView {
-    // Creates a reactive value to update the button
-    let (count, set_count) = create_signal(0);
-    let on_plus = move |_| set_count.update(|count| *count += 1);
-    let on_minus = move |_| set_count.update(|count| *count -= 1);
-
+fn Nav() -> impl IntoView {
     view! {
-        <Title text="Leptos + Tailwindcss"/>
-        <main data-theme="light">
-            <div class="flex flex-col min-h-screen font-mono text-white bg-gradient-to-tl from-gray-900 to-gray-700">
-                <div class="flex flex-row-reverse flex-wrap items-center m-auto">
-                    <button on:click=on_plus class="btn">
-                        "+"
-                    </button>
-                    <div class="px-2">
-                        {count}
-                    </div>
-                    <button on:click=on_minus class="btn">
-                        "-"
-                    </button>
-                </div>
-            </div>
-        </main>
+        <nav class="navbar bg-gray-900/50">
+            <A href="/" class="text-xl btn btn-ghost">"EpicList"</A>
+            <A href="/lists" class="btn btn-ghost">"Lists"</A>
+            <A href="/search" class="btn btn-ghost">"Search"</A>
+            <A href="/feed" class="btn btn-ghost">"Feed"</A>
+        </nav>
     }
 }
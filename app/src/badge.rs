@@ -0,0 +1,546 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+/// Metrics change at most once per crawl, so badges embedded in READMEs can
+/// be cached for a while.
+const CACHE_CONTROL: &str = "public, max-age=3600, stale-while-revalidate=86400";
+
+/// `/badge/:owner/:repo.svg?metric=stars&style=flat-square&label=GitHub`
+#[derive(Debug, Default, Deserialize)]
+pub struct BadgeQuery {
+    /// `lists` (default), `stars`, `last-commit` or `health`
+    pub metric: Option<String>,
+    /// `flat` (default), `flat-square` or `for-the-badge`
+    pub style: Option<String>,
+    /// Replaces the text on the left
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Lists,
+    Stars,
+    LastCommit,
+    Health,
+}
+
+impl Metric {
+    fn parse(metric: Option<&str>) -> Option<Self> {
+        match metric.unwrap_or("lists") {
+            "lists" => Some(Self::Lists),
+            "stars" => Some(Self::Stars),
+            "last-commit" => Some(Self::LastCommit),
+            "health" => Some(Self::Health),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Lists => "listed in",
+            Self::Stars => "stars",
+            Self::LastCommit => "last commit",
+            Self::Health => "health",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Flat,
+    FlatSquare,
+    ForTheBadge,
+}
+
+impl Style {
+    fn parse(style: Option<&str>) -> Self {
+        match style {
+            Some("flat-square") => Self::FlatSquare,
+            Some("for-the-badge") => Self::ForTheBadge,
+            _ => Self::Flat,
+        }
+    }
+}
+
+/// The named colors of shields.io, so the JSON variant can use the names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    BrightGreen,
+    Green,
+    Yellow,
+    Orange,
+    Red,
+    Blue,
+    LightGrey,
+}
+
+impl Color {
+    fn name(self) -> &'static str {
+        match self {
+            Self::BrightGreen => "brightgreen",
+            Self::Green => "green",
+            Self::Yellow => "yellow",
+            Self::Orange => "orange",
+            Self::Red => "red",
+            Self::Blue => "blue",
+            Self::LightGrey => "lightgrey",
+        }
+    }
+
+    fn hex(self) -> &'static str {
+        match self {
+            Self::BrightGreen => "#4c1",
+            Self::Green => "#97ca00",
+            Self::Yellow => "#dfb317",
+            Self::Orange => "#fe7d37",
+            Self::Red => "#e05d44",
+            Self::Blue => "#007ec6",
+            Self::LightGrey => "#9f9f9f",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Badge {
+    label: String,
+    message: String,
+    color: Color,
+}
+
+/// What a badge can show about a project.
+#[derive(Debug, Clone, PartialEq)]
+struct ProjectStats {
+    /// Number of awesome lists linking to the project
+    lists: i64,
+    stars: Option<i32>,
+    pushed_at: Option<DateTime<Utc>>,
+    is_archived: bool,
+}
+
+impl ProjectStats {
+    /// 0 to 100, made up of how many lists recommend the project (30), its
+    /// stars (30) and how recently it was pushed to (40). Archived projects
+    /// get nothing for activity.
+    fn health(&self, now: DateTime<Utc>) -> u32 {
+        let lists = ((1 + self.lists) as f64).ln() / 11f64.ln();
+        let stars = (1.0 + self.stars.unwrap_or_default().max(0) as f64).log10() / 5.0;
+        let activity = match self.pushed_at {
+            Some(pushed_at) if !self.is_archived => {
+                let days = (now - pushed_at).num_days().max(0) as f64;
+                1.0 - ((days - 30.0) / 700.0).clamp(0.0, 1.0)
+            }
+            _ => 0.0,
+        };
+        (lists.min(1.0) * 30.0 + stars.min(1.0) * 30.0 + activity * 40.0).round() as u32
+    }
+
+    fn badge(&self, metric: Metric, now: DateTime<Utc>) -> Badge {
+        let (message, color) = match metric {
+            Metric::Lists => (
+                match self.lists {
+                    1 => "1 awesome list".to_string(),
+                    n => format!("{n} awesome lists"),
+                },
+                match self.lists {
+                    0 => Color::LightGrey,
+                    1 => Color::Blue,
+                    2..=4 => Color::Green,
+                    _ => Color::BrightGreen,
+                },
+            ),
+            Metric::Stars => match self.stars {
+                Some(stars) => (format!("★ {}", compact(stars)), Color::Blue),
+                None => ("unknown".to_string(), Color::LightGrey),
+            },
+            Metric::LastCommit => match self.pushed_at {
+                Some(_) if self.is_archived => ("archived".to_string(), Color::LightGrey),
+                Some(pushed_at) => {
+                    let days = (now - pushed_at).num_days().max(0);
+                    let color = match days {
+                        0..=30 => Color::BrightGreen,
+                        31..=180 => Color::Green,
+                        181..=365 => Color::Yellow,
+                        366..=730 => Color::Orange,
+                        _ => Color::Red,
+                    };
+                    (age(days), color)
+                }
+                None => ("unknown".to_string(), Color::LightGrey),
+            },
+            Metric::Health => {
+                let health = self.health(now);
+                let color = match health {
+                    80.. => Color::BrightGreen,
+                    60..=79 => Color::Green,
+                    40..=59 => Color::Yellow,
+                    20..=39 => Color::Orange,
+                    _ => Color::Red,
+                };
+                (format!("{health}%"), color)
+            }
+        };
+        Badge {
+            label: metric.label().to_string(),
+            message,
+            color,
+        }
+    }
+}
+
+/// `1234` as `1.2k`, like GitHub shows star counts.
+fn compact(n: i32) -> String {
+    match n {
+        1_000_000.. => format!("{:.1}M", n as f64 / 1_000_000.0),
+        10_000.. => format!("{}k", n / 1_000),
+        1_000.. => format!("{:.1}k", n as f64 / 1_000.0),
+        _ => n.to_string(),
+    }
+}
+
+fn age(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        2..=59 => format!("{days} days ago"),
+        60..=729 => format!("{} months ago", days / 30),
+        _ => format!("{} years ago", days / 365),
+    }
+}
+
+async fn project_stats(pool: &PgPool, url: &str) -> Result<Option<ProjectStats>, sqlx::Error> {
+    let lists = sqlx::query_scalar!(
+        r#"--sql
+        SELECT
+            count(DISTINCT awesome_list_id) AS "lists!"
+        FROM
+            current_awesome_links
+        WHERE
+            lower(rtrim(url, '/')) = lower($1)
+        "#,
+        url,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let project = sqlx::query!(
+        r"--sql
+        SELECT
+            stars,
+            pushed_at,
+            is_archived
+        FROM
+            github_projects
+        WHERE
+            lower(url) = lower($1)
+        ",
+        url,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if lists == 0 && project.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(ProjectStats {
+        lists,
+        stars: project.as_ref().map(|p| p.stars),
+        pushed_at: project.as_ref().and_then(|p| p.pushed_at),
+        is_archived: project.is_some_and(|p| p.is_archived),
+    }))
+}
+
+/// Text widths in Verdana 11px, close enough to size the badge without
+/// measuring fonts.
+fn text_width(text: &str) -> f64 {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'l' | 'j' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' | ' ' => 3.5,
+            'f' | 't' | 'r' | 'I' | '(' | ')' => 4.5,
+            'm' | 'w' | 'M' | 'W' | '%' | '★' => 10.0,
+            c if c.is_ascii_uppercase() => 7.5,
+            _ => 6.8,
+        })
+        .sum()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_svg(badge: &Badge, style: Style) -> String {
+    let (label, message, height, font_size, scale, padding) = match style {
+        Style::ForTheBadge => (
+            badge.label.to_uppercase(),
+            badge.message.to_uppercase(),
+            28.0,
+            10,
+            1.2,
+            12.0,
+        ),
+        _ => (
+            badge.label.clone(),
+            badge.message.clone(),
+            20.0,
+            11,
+            1.0,
+            6.0,
+        ),
+    };
+    let label_width = (text_width(&label) * scale + 2.0 * padding).round();
+    let message_width = (text_width(&message) * scale + 2.0 * padding).round();
+    let width = label_width + message_width;
+    let (rx, gradient) = match style {
+        Style::Flat => (3, true),
+        _ => (0, false),
+    };
+    let text_y = height / 2.0 + 4.0;
+    let (label, message) = (escape(&label), escape(&message));
+    let color = badge.color.hex();
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title>"#
+    );
+    if gradient {
+        svg.push_str(r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>"##);
+    }
+    svg.push_str(&format!(
+        r##"<clipPath id="r"><rect width="{width}" height="{height}" rx="{rx}" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="{height}" fill="#555"/><rect x="{label_width}" width="{message_width}" height="{height}" fill="{color}"/>"##
+    ));
+    if gradient {
+        svg.push_str(&format!(
+            r#"<rect width="{width}" height="{height}" fill="url(#s)"/>"#
+        ));
+    }
+    svg.push_str(&format!(
+        r##"</g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="{font_size}"><text x="{}" y="{text_y}">{label}</text><text x="{}" y="{text_y}">{message}</text></g></svg>"##,
+        label_width / 2.0,
+        label_width + message_width / 2.0,
+    ));
+    svg
+}
+
+/// The JSON shields.io renders through its endpoint badge,
+/// `https://img.shields.io/endpoint?url=...`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShieldsEndpoint {
+    schema_version: u8,
+    label: String,
+    message: String,
+    color: &'static str,
+}
+
+/// Renders a badge for `https://github.com/:owner/:repo`, as SVG for
+/// `/badge/:owner/:repo.svg` and as a shields.io endpoint for
+/// `/badge/:owner/:repo.json`.
+pub async fn badge(
+    State(pool): State<PgPool>,
+    Path((owner, file)): Path<(String, String)>,
+    Query(query): Query<BadgeQuery>,
+    headers: HeaderMap,
+) -> Response {
+    // the router can't match a parameter followed by an extension
+    let (repo, json) = match (file.strip_suffix(".svg"), file.strip_suffix(".json")) {
+        (Some(repo), _) => (repo, false),
+        (_, Some(repo)) => (repo, true),
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    let Some(metric) = Metric::parse(query.metric.as_deref()) else {
+        return (StatusCode::BAD_REQUEST, "unknown metric").into_response();
+    };
+    let style = Style::parse(query.style.as_deref());
+
+    let url = format!("https://github.com/{owner}/{repo}");
+    let (status, mut badge) = match project_stats(&pool, &url).await {
+        Ok(Some(stats)) => (StatusCode::OK, stats.badge(metric, Utc::now())),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Badge {
+                label: metric.label().to_string(),
+                message: "not found".to_string(),
+                color: Color::LightGrey,
+            },
+        ),
+        Err(e) => {
+            log::error!("badge for {url}: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if let Some(label) = query.label {
+        badge.label = label;
+    }
+
+    let (content_type, body) = if json {
+        let endpoint = ShieldsEndpoint {
+            schema_version: 1,
+            label: badge.label,
+            message: badge.message,
+            color: badge.color.name(),
+        };
+        (
+            "application/json",
+            serde_json::to_string(&endpoint).expect("serializable"),
+        )
+    } else {
+        ("image/svg+xml; charset=utf-8", render_svg(&badge, style))
+    };
+
+    let etag = format!("\"{:x}\"", Sha256::digest(body.as_bytes()));
+    let cache_headers = [
+        (header::ETAG, HeaderValue::from_str(&etag).unwrap()),
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(CACHE_CONTROL),
+        ),
+    ];
+    if status == StatusCode::OK
+        && headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|tags| tags.to_str().ok())
+            .is_some_and(|tags| none_match(tags, &etag))
+    {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+
+    (
+        status,
+        cache_headers,
+        [(header::CONTENT_TYPE, content_type)],
+        body,
+    )
+        .into_response()
+}
+
+/// Whether an `If-None-Match` header lists the tag, comparing weakly as the
+/// header asks for: `W/"a"` matches `"a"` and `*` matches anything.
+fn none_match(tags: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    tags.split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2023-10-01T00:00:00Z".parse().unwrap()
+    }
+
+    fn stats(lists: i64, stars: Option<i32>, days_ago: Option<i64>) -> ProjectStats {
+        ProjectStats {
+            lists,
+            stars,
+            pushed_at: days_ago.map(|days| now() - chrono::Duration::days(days)),
+            is_archived: false,
+        }
+    }
+
+    #[test]
+    fn test_health() {
+        assert_eq!(stats(0, None, None).health(now()), 0);
+        // 10 lists, 100k stars and pushed within a month is as healthy as it gets
+        assert_eq!(stats(10, Some(100_000), Some(3)).health(now()), 100);
+        assert_eq!(stats(50, Some(500_000), Some(0)).health(now()), 100);
+        // activity fades over two years after the first month
+        assert_eq!(stats(10, Some(100_000), Some(380)).health(now()), 80);
+        assert_eq!(stats(10, Some(100_000), Some(730)).health(now()), 60);
+        assert_eq!(stats(1, Some(99), Some(10_000)).health(now()), 21);
+
+        let archived = ProjectStats {
+            is_archived: true,
+            ..stats(10, Some(100_000), Some(3))
+        };
+        assert_eq!(archived.health(now()), 60);
+    }
+
+    #[test]
+    fn test_badge() {
+        let badge = stats(1, Some(1234), Some(45)).badge(Metric::Lists, now());
+        assert_eq!(badge.message, "1 awesome list");
+        assert_eq!(badge.color, Color::Blue);
+
+        let badge = stats(1, Some(1234), Some(45)).badge(Metric::Stars, now());
+        assert_eq!(badge.message, "★ 1.2k");
+
+        let badge = stats(1, Some(1234), Some(45)).badge(Metric::LastCommit, now());
+        assert_eq!(badge.message, "45 days ago");
+        assert_eq!(badge.color, Color::Green);
+
+        let badge = stats(0, None, None).badge(Metric::LastCommit, now());
+        assert_eq!(
+            (badge.message.as_str(), badge.color),
+            ("unknown", Color::LightGrey)
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        assert_eq!(compact(0), "0");
+        assert_eq!(compact(999), "999");
+        assert_eq!(compact(1_000), "1.0k");
+        assert_eq!(compact(1_250), "1.2k");
+        assert_eq!(compact(9_999), "10.0k");
+        assert_eq!(compact(10_000), "10k");
+        assert_eq!(compact(123_456), "123k");
+        assert_eq!(compact(2_500_000), "2.5M");
+    }
+
+    #[test]
+    fn test_age() {
+        assert_eq!(age(0), "today");
+        assert_eq!(age(1), "yesterday");
+        assert_eq!(age(59), "59 days ago");
+        assert_eq!(age(60), "2 months ago");
+        assert_eq!(age(729), "24 months ago");
+        assert_eq!(age(730), "2 years ago");
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let badge = Badge {
+            label: "a&b".to_string(),
+            message: "<1>".to_string(),
+            color: Color::Red,
+        };
+
+        let svg = render_svg(&badge, Style::Flat);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains("<title>a&amp;b: &lt;1&gt;</title>"));
+        assert!(svg.contains(r##"fill="#e05d44""##));
+        assert!(svg.contains(r#"rx="3""#));
+        assert!(svg.contains("linearGradient"));
+
+        let svg = render_svg(&badge, Style::FlatSquare);
+        assert!(svg.contains(r#"rx="0""#));
+        assert!(!svg.contains("linearGradient"));
+
+        let svg = render_svg(&badge, Style::ForTheBadge);
+        assert!(svg.contains(r#"height="28""#));
+        assert!(svg.contains(">A&amp;B</text>"));
+    }
+
+    #[test]
+    fn test_none_match() {
+        let etag = r#""abc""#;
+        assert!(none_match(r#""abc""#, etag));
+        assert!(none_match(r#"W/"abc""#, etag));
+        assert!(none_match(r#""xyz", W/"abc""#, etag));
+        assert!(none_match("*", etag));
+        assert!(!none_match(r#""xyz""#, etag));
+        assert!(!none_match(r#""abcd", "ab""#, etag));
+        assert!(!none_match("", etag));
+    }
+}
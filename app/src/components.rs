@@ -0,0 +1,167 @@
+use leptos::*;
+use search::SearchHit;
+
+/// Links found by a search, best match first, with the search terms marked
+/// in titles and descriptions.
+#[component]
+pub fn SearchHits(
+    hits: Vec<SearchHit>,
+    /// Lowercase words to mark
+    #[prop(optional)]
+    terms: Vec<String>,
+) -> impl IntoView {
+    if hits.is_empty() {
+        return view! { <p class="text-gray-400">"Nothing found."</p> }.into_view();
+    }
+
+    view! {
+        <ol class="flex flex-col gap-4">
+            {hits
+                .into_iter()
+                .map(|hit| {
+                    view! {
+                        <li>
+                            <a href=hit.url.clone() class="font-bold hover:underline">
+                                {highlight(&hit.title, &terms)}
+                            </a>
+                            {hit
+                                .language
+                                .map(|language| {
+                                    view! { <span class="ml-2 text-xs text-gray-400">{language}</span> }
+                                })}
+                            <div class="text-sm text-gray-400">
+                                {hit.list} " › " {hit.breadcrumbs.join(" › ")}
+                            </div>
+                            {hit.description.map(|description| view! { <p>{highlight(&description, &terms)}</p> })}
+                        </li>
+                    }
+                })
+                .collect_view()}
+        </ol>
+    }
+    .into_view()
+}
+
+/// Wraps every occurrence of the terms in `<mark>`, ignoring ASCII case.
+pub fn highlight(text: &str, terms: &[String]) -> View {
+    highlight_parts(text, terms)
+        .into_iter()
+        .map(|(part, marked)| {
+            let part = part.to_string();
+            if marked {
+                view! { <mark class="text-black bg-yellow-300">{part}</mark> }.into_view()
+            } else {
+                part.into_view()
+            }
+        })
+        .collect_view()
+}
+
+/// Splits the text into runs that are or aren't one of the terms, preferring
+/// the longest term starting at a position.
+fn highlight_parts<'a>(text: &'a str, terms: &[String]) -> Vec<(&'a str, bool)> {
+    let lower = text.to_ascii_lowercase();
+    let mut parts = vec![];
+    let (mut start, mut i) = (0, 0);
+    while i < text.len() {
+        let matched = terms
+            .iter()
+            .filter(|t| !t.is_empty() && lower[i..].starts_with(t.as_str()))
+            .map(String::len)
+            .max();
+        match matched {
+            Some(len) => {
+                if start < i {
+                    parts.push((&text[start..i], false));
+                }
+                parts.push((&text[i..i + len], true));
+                i += len;
+                start = i;
+            }
+            None => i += text[i..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    if start < text.len() {
+        parts.push((&text[start..], false));
+    }
+    parts
+}
+
+/// Shows the errors of the server functions below it, the `ErrorTemplate`
+/// only knows about routing errors.
+#[component]
+pub fn ServerErrors(errors: RwSignal<Errors>) -> impl IntoView {
+    view! {
+        <ul class="text-red-400">
+            {move || {
+                errors
+                    .get()
+                    .into_iter()
+                    .map(|(_, e)| view! { <li>{e.to_string()}</li> })
+                    .collect_view()
+            }}
+        </ul>
+    }
+}
+
+/// Tells crawlers the page doesn't exist, routes using it should render with
+/// `SsrMode::Async` so the status is set before the response starts.
+#[component]
+pub fn NotFound(#[prop(into)] what: String) -> impl IntoView {
+    #[cfg(feature = "ssr")]
+    if let Some(response) = use_context::<leptos_axum::ResponseOptions>() {
+        response.set_status(http::StatusCode::NOT_FOUND);
+    }
+
+    view! { <p class="text-gray-400">{what} " not found."</p> }
+}
+
+/// A symbol for each `AwesomeLinkType`.
+pub fn link_type_icon(link_type: Option<&str>) -> &'static str {
+    match link_type {
+        Some("Repo") => "📦",
+        Some("Article") => "📄",
+        Some("Video") => "🎬",
+        Some("Podcast") => "🎙️",
+        Some("Book") => "📚",
+        Some("Course") => "🎓",
+        _ => "🔗",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(terms: &[&str]) -> Vec<String> {
+        terms.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_highlight_parts() {
+        assert_eq!(
+            highlight_parts("Async I/O for Rust, async everywhere", &terms(&["async"])),
+            [
+                ("Async", true),
+                (" I/O for Rust, ", false),
+                ("async", true),
+                (" everywhere", false)
+            ]
+        );
+        // the longer term wins
+        assert_eq!(
+            highlight_parts("asynchronous", &terms(&["async", "asynchronous"])),
+            [("asynchronous", true)]
+        );
+        // multi-byte characters are skipped whole
+        assert_eq!(
+            highlight_parts("café › web", &terms(&["web"])),
+            [("café › ", false), ("web", true)]
+        );
+        assert_eq!(
+            highlight_parts("nothing", &terms(&["", "else"])),
+            [("nothing", false)]
+        );
+        assert_eq!(highlight_parts("", &terms(&["a"])), []);
+    }
+}
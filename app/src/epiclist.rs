@@ -0,0 +1,620 @@
+use crate::components::{link_type_icon, ServerErrors};
+use crate::projects::project_path;
+use crate::tree::{category_tree, Category};
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+/// Only the most endorsed projects of each category are shown.
+const PER_CATEGORY: usize = 25;
+
+/// A project recommended by one or more lists.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpicProject {
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub link_type: Option<String>,
+    /// Every list linking to it as `owner/repo`, in any category
+    pub lists: Vec<String>,
+    pub stars: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpicCategory {
+    pub path: Vec<String>,
+    pub projects: Vec<EpicProject>,
+    /// Projects in the category before cutting it down to `PER_CATEGORY`
+    pub total: usize,
+}
+
+/// Top-level categories per page, each with everything below it.
+const PER_PAGE: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpicPage {
+    pub categories: Vec<EpicCategory>,
+    /// Top-level categories across all pages
+    pub total: usize,
+}
+
+/// Merging reads every current link, so it is done once and the result kept
+/// until a crawl, an enrich run or a deleted list moves the version.
+#[cfg(feature = "ssr")]
+static MERGED: std::sync::Mutex<Option<std::sync::Arc<merge::Merged>>> =
+    std::sync::Mutex::new(None);
+
+#[server(GetEpicList, "/api")]
+pub async fn get_epic_list(min_lists: usize, page: usize) -> Result<EpicPage, ServerFnError> {
+    use std::sync::Arc;
+
+    let pool = crate::state::pool()?;
+
+    let version = sqlx::query_as!(
+        merge::Version,
+        r#"--sql
+        SELECT
+            max(crawled_at) AS crawled_at,
+            count(*) AS "lists!",
+            (SELECT max(enriched_at) FROM github_projects) AS enriched_at
+        FROM
+            awesome_lists
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    let cached = MERGED
+        .lock()
+        .unwrap()
+        .clone()
+        .filter(|merged| merged.version == version);
+    let merged = match cached {
+        Some(merged) => merged,
+        None => {
+            let links = sqlx::query!(
+                r#"--sql
+                SELECT
+                    l.url AS "url!",
+                    l.title,
+                    l.description,
+                    l.breadcrumbs AS "breadcrumbs!",
+                    l.link_type,
+                    al.owner,
+                    al.repo,
+                    gp.stars AS "stars?"
+                FROM
+                    current_awesome_links l
+                    JOIN awesome_lists al ON al.id = l.awesome_list_id
+                    LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+                "#,
+            )
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+            let merged = Arc::new(merge::merge(
+                links.into_iter().map(|l| merge::ListedLink {
+                    url: l.url,
+                    title: l.title.unwrap_or_default(),
+                    description: l.description,
+                    breadcrumbs: l.breadcrumbs,
+                    link_type: l.link_type,
+                    list: format!(
+                        "{}/{}",
+                        l.owner.unwrap_or_default(),
+                        l.repo.unwrap_or_default()
+                    ),
+                    stars: l.stars,
+                }),
+                version,
+            ));
+            *MERGED.lock().unwrap() = Some(merged.clone());
+            merged
+        }
+    };
+
+    Ok(merge::page(merged.rank(min_lists), page))
+}
+
+#[cfg(feature = "ssr")]
+mod merge {
+    use super::{EpicCategory, EpicPage, EpicProject, PER_CATEGORY, PER_PAGE};
+    use std::collections::{BTreeSet, HashMap, HashSet};
+
+    /// Words that only say a category holds projects, "Web frameworks" and
+    /// "Web libraries" are the same category.
+    const FILLER_WORDS: &[&str] = &[
+        "and",
+        "application",
+        "applications",
+        "development",
+        "framework",
+        "frameworks",
+        "libraries",
+        "library",
+        "misc",
+        "miscellaneous",
+        "other",
+        "programming",
+        "project",
+        "projects",
+        "resources",
+        "tool",
+        "tools",
+    ];
+
+    pub struct ListedLink {
+        pub url: String,
+        pub title: String,
+        pub description: Option<String>,
+        pub breadcrumbs: Vec<String>,
+        pub link_type: Option<String>,
+        /// As `owner/repo`
+        pub list: String,
+        pub stars: Option<i32>,
+    }
+
+    /// Lowercase words without punctuation or filler, keeping `+` and `#`
+    /// so C++ and C# stay apart from C.
+    fn normalize(segment: &str) -> String {
+        segment
+            .chars()
+            .filter(|c| c.is_alphanumeric() || c.is_whitespace() || matches!(c, '+' | '#'))
+            .collect::<String>()
+            .to_lowercase()
+            .split_whitespace()
+            .filter(|w| !FILLER_WORDS.contains(w))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The same URL written slightly differently by different lists.
+    fn url_key(url: &str) -> String {
+        url.trim_end_matches('/')
+            .trim_end_matches(".git")
+            .trim_start_matches("http://")
+            .trim_start_matches("https://")
+            .trim_start_matches("www.")
+            .to_lowercase()
+    }
+
+    /// The language a list is about, `awesome-rust` is about Rust.
+    fn list_language(list: &str) -> Option<&str> {
+        list.split_once('/')?.1.strip_prefix("awesome-")
+    }
+
+    /// Normalized category with the language first, so "Web > Rust" from a
+    /// web list and "Web programming" from awesome-rust both end up under
+    /// "rust > web".
+    fn category_key(
+        link: &ListedLink,
+        languages: &HashSet<String>,
+        display: &mut HashMap<String, String>,
+    ) -> Vec<String> {
+        let mut language = None;
+        let mut topics = vec![];
+        for crumb in &link.breadcrumbs {
+            let key = normalize(crumb);
+            if key.is_empty() {
+                continue;
+            }
+            display.entry(key.clone()).or_insert_with(|| crumb.clone());
+            if language.is_none() && languages.contains(&key) {
+                language = Some(key);
+            } else if topics.last() != Some(&key) {
+                topics.push(key);
+            }
+        }
+
+        let language = language.or_else(|| {
+            let language = list_language(&link.list)?;
+            let key = normalize(language);
+            let mut name = language.to_string();
+            if let Some(first) = name.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            display.entry(key.clone()).or_insert(name);
+            Some(key)
+        });
+        language.into_iter().chain(topics).collect()
+    }
+
+    /// What every writer moves: a crawl, a deleted list or an enrich run.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct Version {
+        pub crawled_at: Option<chrono::DateTime<chrono::Utc>>,
+        pub lists: i64,
+        pub enriched_at: Option<chrono::DateTime<chrono::Utc>>,
+    }
+
+    /// Every project once per normalized category, before ranking.
+    pub struct Merged {
+        /// The data the merge was built from, it is stale once that moves
+        pub version: Version,
+        projects: HashMap<String, EpicProject>,
+        categories: HashMap<Vec<String>, BTreeSet<String>>,
+        /// Normalized category names to how a list spelled them first
+        display: HashMap<String, String>,
+    }
+
+    pub fn merge(links: impl Iterator<Item = ListedLink>, version: Version) -> Merged {
+        let links = links.collect::<Vec<_>>();
+        let languages = links
+            .iter()
+            .filter_map(|l| list_language(&l.list))
+            .map(normalize)
+            .collect::<HashSet<_>>();
+
+        let mut display = HashMap::new();
+        let mut projects: HashMap<String, EpicProject> = HashMap::new();
+        let mut categories: HashMap<Vec<String>, BTreeSet<String>> = HashMap::new();
+        for link in links {
+            let key = url_key(&link.url);
+            let category = category_key(&link, &languages, &mut display);
+            categories.entry(category).or_default().insert(key.clone());
+
+            let project = projects.entry(key).or_insert_with(|| EpicProject {
+                url: link.url.clone(),
+                title: link.title.clone(),
+                description: None,
+                link_type: link.link_type.clone(),
+                lists: vec![],
+                stars: link.stars,
+            });
+            if !project.lists.contains(&link.list) {
+                project.lists.push(link.list);
+            }
+            // the most detailed description any list gives
+            if link.description.as_ref().map(String::len)
+                > project.description.as_ref().map(String::len)
+            {
+                project.description = link.description;
+            }
+        }
+
+        Merged {
+            version,
+            projects,
+            categories,
+            display,
+        }
+    }
+
+    impl Merged {
+        /// The categories with projects in at least `min_lists` lists, biggest
+        /// first, each ranked by endorsements and cut to `PER_CATEGORY`.
+        pub fn rank(&self, min_lists: usize) -> Vec<EpicCategory> {
+            let mut ranked = self
+                .categories
+                .iter()
+                .filter_map(|(path, keys)| {
+                    let mut projects = keys
+                        .iter()
+                        .map(|k| &self.projects[k])
+                        .filter(|p| p.lists.len() >= min_lists)
+                        .collect::<Vec<_>>();
+                    if projects.is_empty() {
+                        return None;
+                    }
+                    projects.sort_by(|a, b| {
+                        b.lists
+                            .len()
+                            .cmp(&a.lists.len())
+                            .then(b.stars.cmp(&a.stars))
+                            .then(a.title.cmp(&b.title))
+                    });
+                    Some(EpicCategory {
+                        path: path.iter().map(|k| self.display[k].clone()).collect(),
+                        total: projects.len(),
+                        projects: projects.into_iter().take(PER_CATEGORY).cloned().collect(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            // biggest categories first, the tree keeps that order
+            ranked.sort_by(|a, b| b.total.cmp(&a.total).then(a.path.cmp(&b.path)));
+            ranked
+        }
+    }
+
+    /// Pages through top-level categories, so a page always holds complete
+    /// subtrees. They come in the order of their biggest category.
+    pub fn page(categories: Vec<EpicCategory>, page: usize) -> EpicPage {
+        let mut tops: Vec<String> = vec![];
+        for category in &categories {
+            let top = category.path.first().cloned().unwrap_or_default();
+            if !tops.contains(&top) {
+                tops.push(top);
+            }
+        }
+        let shown = tops
+            .iter()
+            .skip(page * PER_PAGE)
+            .take(PER_PAGE)
+            .collect::<HashSet<_>>();
+
+        EpicPage {
+            total: tops.len(),
+            categories: categories
+                .into_iter()
+                .filter(|c| shown.contains(&c.path.first().cloned().unwrap_or_default()))
+                .collect(),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn link(url: &str, breadcrumbs: &[&str], list: &str) -> ListedLink {
+            ListedLink {
+                url: url.to_string(),
+                title: url.rsplit('/').next().unwrap().to_string(),
+                description: None,
+                breadcrumbs: breadcrumbs.iter().map(|b| b.to_string()).collect(),
+                link_type: None,
+                list: list.to_string(),
+                stars: None,
+            }
+        }
+
+        #[test]
+        fn test_normalize() {
+            assert_eq!(normalize("Web Frameworks"), "web");
+            assert_eq!(normalize("Web libraries & tools"), "web");
+            assert_eq!(normalize("C++"), "c++");
+            assert_eq!(normalize("C#"), "c#");
+            assert_eq!(normalize("  Rust:  "), "rust");
+            assert_eq!(normalize("Miscellaneous"), "");
+        }
+
+        #[test]
+        fn test_category_key() {
+            let languages = HashSet::from(["rust".to_string(), "python".to_string()]);
+            let mut display = HashMap::new();
+
+            // the language comes from the list when the breadcrumbs lack one
+            let from_list = link("https://a", &["Web programming"], "o/awesome-rust");
+            assert_eq!(
+                category_key(&from_list, &languages, &mut display),
+                ["rust", "web"]
+            );
+            // and is moved to the front when they have one
+            let from_crumbs = link("https://b", &["Web", "Rust"], "o/web-resources");
+            assert_eq!(
+                category_key(&from_crumbs, &languages, &mut display),
+                ["rust", "web"]
+            );
+            assert_eq!(display["rust"], "Rust");
+            assert_eq!(display["web"], "Web programming");
+
+            // filler-only crumbs and repeats disappear
+            let noisy = link("https://c", &["Tools", "Web", "Web frameworks"], "o/list");
+            assert_eq!(category_key(&noisy, &languages, &mut display), ["web"]);
+        }
+
+        #[test]
+        fn test_merge() {
+            let merged = merge(
+                [
+                    link(
+                        "https://github.com/tokio-rs/axum",
+                        &["Web"],
+                        "a/awesome-rust",
+                    ),
+                    link(
+                        "http://github.com/Tokio-rs/axum/",
+                        &["Web", "Rust"],
+                        "b/web-resources",
+                    ),
+                    link(
+                        "https://github.com/actix/actix-web",
+                        &["Web"],
+                        "a/awesome-rust",
+                    ),
+                    link(
+                        "https://github.com/pallets/flask",
+                        &["Web"],
+                        "c/awesome-python",
+                    ),
+                ]
+                .into_iter(),
+                Version::default(),
+            );
+
+            let ranked = merged.rank(1);
+            let paths = ranked
+                .iter()
+                .map(|c| c.path.join(" > "))
+                .collect::<Vec<_>>();
+            assert_eq!(paths, ["Rust > Web", "Python > Web"]);
+            let rust_web = &ranked[0];
+            assert_eq!(rust_web.total, 2);
+            // endorsed by two lists, so it is first
+            assert_eq!(rust_web.projects[0].title, "axum");
+            assert_eq!(
+                rust_web.projects[0].lists,
+                ["a/awesome-rust", "b/web-resources"]
+            );
+
+            let endorsed = merged.rank(2);
+            assert_eq!(endorsed.len(), 1);
+            assert_eq!(endorsed[0].projects.len(), 1);
+        }
+
+        #[test]
+        fn test_page() {
+            let category = |path: &[&str]| EpicCategory {
+                path: path.iter().map(|p| p.to_string()).collect(),
+                projects: vec![],
+                total: 1,
+            };
+            let mut categories = vec![category(&["Rust", "Web"])];
+            for i in 0..PER_PAGE {
+                categories.push(category(&[&format!("Topic {i}")]));
+            }
+            categories.push(category(&["Rust"]));
+
+            let first = page(categories.clone(), 0);
+            assert_eq!(first.total, PER_PAGE + 1);
+            assert_eq!(first.categories.len(), PER_PAGE + 1);
+            assert_eq!(first.categories.last().unwrap().path, ["Rust"]);
+
+            let second = page(categories, 1);
+            assert_eq!(second.categories.len(), 1);
+            assert_eq!(
+                second.categories[0].path,
+                [format!("Topic {}", PER_PAGE - 1)]
+            );
+        }
+    }
+}
+
+/// The single list of all awesome lists: every linked project once per
+/// category, ranked by how many lists recommend it.
+#[component]
+pub fn EpicList() -> impl IntoView {
+    let query = use_query_map();
+    let param = move |key: &str| query.with(|q| q.get(key).and_then(|v| v.parse::<usize>().ok()));
+    let min_lists = move || param("min").unwrap_or(1);
+    let page = move || param("page").unwrap_or(0);
+    let categories = create_resource(
+        move || (min_lists(), page()),
+        |(min_lists, page)| get_epic_list(min_lists, page),
+    );
+    let page_href = move |page: usize| format!("/?min={}&page={page}", min_lists());
+
+    view! {
+        <Title text="All awesome lists in one"/>
+        <h1 class="text-2xl">"EpicList"</h1>
+        <p>"Every project from every awesome list, merged into one set of categories."</p>
+        <div class="flex gap-2">
+            {[1, 2, 3]
+                .into_iter()
+                .map(|min| {
+                    view! {
+                        <A
+                            href=format!("/?min={min}")
+                            class=move || {
+                                if min_lists() == min { "btn btn-sm btn-active" } else { "btn btn-sm" }
+                            }
+                        >
+                            {if min == 1 { "All".to_string() } else { format!("In {min}+ lists") }}
+                        </A>
+                    }
+                })
+                .collect_view()}
+        </div>
+        <Transition fallback=move || view! { <p>"Loading..."</p> }>
+            <ErrorBoundary fallback=|errors| view! { <ServerErrors errors/> }>
+                {move || {
+                    categories
+                        .get()
+                        .map(|categories| {
+                            categories
+                                .map(|categories| {
+                                    let pages = (categories.total + PER_PAGE - 1) / PER_PAGE;
+                                    let tree = category_tree(
+                                        categories
+                                            .categories
+                                            .into_iter()
+                                            .map(|c| (c.path.clone(), c)),
+                                    );
+                                    view! {
+                                        {category_view(tree)}
+                                        <div class="join">
+                                            <Show when=move || { page() > 0 } fallback=|| ()>
+                                                <A href=move || page_href(page() - 1) class="join-item btn">
+                                                    "«"
+                                                </A>
+                                            </Show>
+                                            <span class="join-item btn btn-disabled">
+                                                {move || page() + 1} " / " {pages.max(1)}
+                                            </span>
+                                            <Show when=move || { page() + 1 < pages } fallback=|| ()>
+                                                <A href=move || page_href(page() + 1) class="join-item btn">
+                                                    "»"
+                                                </A>
+                                            </Show>
+                                        </div>
+                                    }
+                                })
+                        })
+                }}
+            </ErrorBoundary>
+        </Transition>
+    }
+}
+
+fn category_view(category: Category<EpicCategory>) -> View {
+    let Category {
+        name,
+        items,
+        children,
+    } = category;
+    let content = view! {
+        {items.into_iter().map(projects_view).collect_view()}
+        {children.into_iter().map(category_view).collect_view()}
+    };
+
+    if name.is_empty() {
+        return content.into_view();
+    }
+    view! {
+        <details class="ml-4" open>
+            <summary class="cursor-pointer">{name}</summary>
+            {content}
+        </details>
+    }
+    .into_view()
+}
+
+fn projects_view(category: EpicCategory) -> impl IntoView {
+    let hidden = category.total - category.projects.len();
+    view! {
+        <ul class="ml-2">
+            {category
+                .projects
+                .into_iter()
+                .map(|project| {
+                    view! {
+                        <li class="my-1">
+                            <span title=project.link_type.clone()>
+                                {link_type_icon(project.link_type.as_deref())}
+                            </span>
+                            " "
+                            <a href=project.url.clone() class="hover:underline">
+                                {project.title}
+                            </a>
+                            {project_path(&project.url)
+                                .map(|p| {
+                                    view! {
+                                        <A href=p class="ml-1 text-xs text-gray-400 hover:underline">
+                                            "details"
+                                        </A>
+                                    }
+                                })}
+                            {project.stars.map(|s| view! { <span class="ml-2 text-xs">"★ " {s}</span> })}
+                            {project.description.map(|d| view! { <span class="text-gray-300">" - " {d}</span> })}
+                            <span class="ml-2">
+                                {project
+                                    .lists
+                                    .into_iter()
+                                    .map(|list| {
+                                        view! {
+                                            <A href=format!("/lists/{list}") class="mr-1 badge badge-outline badge-sm">
+                                                {list}
+                                            </A>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </span>
+                        </li>
+                    }
+                })
+                .collect_view()}
+            {(hidden > 0).then(|| view! { <li class="text-sm text-gray-400">"and " {hidden} " more"</li> })}
+        </ul>
+    }
+}
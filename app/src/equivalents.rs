@@ -0,0 +1,83 @@
+use crate::components::{SearchHits, ServerErrors};
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+use search::SearchHit;
+
+#[server(GetEquivalents, "/api")]
+pub async fn get_equivalents(
+    owner: String,
+    repo: String,
+    language: Option<String>,
+) -> Result<Vec<SearchHit>, ServerFnError> {
+    let pool = crate::state::pool()?;
+    let query = search::EquivalentsQuery {
+        url: format!("https://github.com/{owner}/{repo}"),
+        language: language.filter(|l| !l.is_empty()),
+        limit: 20,
+    };
+    search::equivalents(&pool, &query)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+#[derive(Params, PartialEq, Clone)]
+struct ProjectParams {
+    owner: Option<String>,
+    repo: Option<String>,
+}
+
+impl ProjectParams {
+    fn project(&self) -> Option<(String, String)> {
+        Some((self.owner.clone()?, self.repo.clone()?))
+    }
+}
+
+/// Projects in other languages that fill the same role, like the Python
+/// equivalent of lodash.
+#[component]
+pub fn Equivalents() -> impl IntoView {
+    let params = use_params::<ProjectParams>();
+    let query = use_query_map();
+    let project = move || params.with(|p| p.as_ref().ok().and_then(ProjectParams::project));
+    let name = move || {
+        project()
+            .map(|(owner, repo)| format!("{owner}/{repo}"))
+            .unwrap_or_default()
+    };
+
+    let equivalents = create_resource(
+        move || {
+            (
+                project(),
+                query.with(|q| q.get("language").cloned()),
+            )
+        },
+        |(project, language)| async move {
+            match project {
+                Some((owner, repo)) => get_equivalents(owner, repo, language).await,
+                None => Ok(vec![]),
+            }
+        },
+    );
+
+    view! {
+        <Title text=move || format!("Equivalents of {}", name())/>
+        <h1 class="text-2xl">"Equivalents of " {name}</h1>
+        <Form method="GET" action="">
+            <input
+                type="text"
+                name="language"
+                placeholder="in any other language"
+                class="px-2 text-black"
+                prop:value=move || query.with(|q| q.get("language").cloned().unwrap_or_default())
+            />
+            <button type="submit" class="ml-2 btn">"Find"</button>
+        </Form>
+        <Suspense fallback=move || view! { <p>"Loading..."</p> }>
+            <ErrorBoundary fallback=|errors| view! { <ServerErrors errors/> }>
+                {move || equivalents.get().map(|hits| hits.map(|hits| view! { <SearchHits hits/> }))}
+            </ErrorBoundary>
+        </Suspense>
+    }
+}
@@ -0,0 +1,515 @@
+use crate::components::{link_type_icon, ServerErrors};
+use crate::projects::project_path;
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+/// Holds the anonymous follower id until there are accounts.
+#[cfg(feature = "ssr")]
+const FOLLOWER_COOKIE: &str = "epiclist_follower";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FollowKind {
+    List,
+    Category,
+    Project,
+}
+
+impl FollowKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FollowKind::List => "list",
+            FollowKind::Category => "category",
+            FollowKind::Project => "project",
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "list" => Some(FollowKind::List),
+            "category" => Some(FollowKind::Category),
+            "project" => Some(FollowKind::Project),
+            _ => None,
+        }
+    }
+}
+
+/// Something whose new links show up in the feed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Follow {
+    pub kind: FollowKind,
+    /// `owner/repo` of the list for lists and categories, the url for
+    /// projects
+    pub target: String,
+    /// The category, everything below it is followed too
+    pub breadcrumbs: Vec<String>,
+}
+
+/// A link that was added, moved or got a new description.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedItem {
+    /// One of `added`, `moved` or `description_changed`
+    pub kind: String,
+    pub occurred_at: DateTime<Utc>,
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub link_type: Option<String>,
+    /// As `owner/repo`
+    pub list: String,
+    pub breadcrumbs: Vec<String>,
+    /// Where a moved link was before
+    pub old_breadcrumbs: Option<Vec<String>>,
+}
+
+/// The follower id from the request's cookies.
+#[cfg(feature = "ssr")]
+fn follower_id() -> Option<uuid::Uuid> {
+    let parts = use_context::<http::request::Parts>()?;
+    parts
+        .headers
+        .get_all(http::header::COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(';'))
+        .find_map(|cookie| {
+            cookie
+                .trim()
+                .strip_prefix(FOLLOWER_COOKIE)?
+                .strip_prefix('=')?
+                .parse()
+                .ok()
+        })
+}
+
+#[server(GetFollows, "/api")]
+pub async fn get_follows() -> Result<Vec<Follow>, ServerFnError> {
+    let Some(follower_id) = follower_id() else {
+        return Ok(vec![]);
+    };
+    let pool = crate::state::pool()?;
+    follows(&pool, follower_id)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+/// Everything the follower follows, oldest first.
+#[cfg(feature = "ssr")]
+pub async fn follows(
+    pool: &sqlx::PgPool,
+    follower_id: uuid::Uuid,
+) -> Result<Vec<Follow>, sqlx::Error> {
+    let follows = sqlx::query!(
+        r#"--sql
+        SELECT
+            kind,
+            target,
+            breadcrumbs AS "breadcrumbs!"
+        FROM
+            follows
+        WHERE
+            follower_id = $1
+        ORDER BY
+            created_at
+        "#,
+        follower_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(follows
+        .into_iter()
+        .filter_map(|f| {
+            Some(Follow {
+                kind: FollowKind::parse(&f.kind)?,
+                target: f.target,
+                breadcrumbs: f.breadcrumbs,
+            })
+        })
+        .collect())
+}
+
+/// Hands out a follower id on the first follow. CBOR encoded, the url
+/// encoding can't tell an empty list of breadcrumbs from a missing one.
+#[server(AddFollow, "/api", "Cbor")]
+pub async fn add_follow(follow: Follow) -> Result<(), ServerFnError> {
+    let pool = crate::state::pool()?;
+    let follower_id = match follower_id() {
+        Some(follower_id) => follower_id,
+        None => {
+            let follower_id = sqlx::query_scalar!(
+                r#"--sql
+                SELECT
+                    uuid_generate_v4() AS "id!"
+                "#
+            )
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+            let cookie = format!(
+                "{FOLLOWER_COOKIE}={follower_id}; Path=/; Max-Age=31536000; HttpOnly; SameSite=Lax"
+            );
+            expect_context::<leptos_axum::ResponseOptions>().append_header(
+                http::header::SET_COOKIE,
+                http::HeaderValue::from_str(&cookie)
+                    .map_err(|e| ServerFnError::ServerError(e.to_string()))?,
+            );
+            follower_id
+        }
+    };
+    follow_as(&pool, follower_id, &follow)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+/// Following the same thing twice is a no-op.
+#[cfg(feature = "ssr")]
+pub async fn follow_as(
+    pool: &sqlx::PgPool,
+    follower_id: uuid::Uuid,
+    follow: &Follow,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r"--sql
+        INSERT INTO follows(follower_id, kind, target, breadcrumbs)
+            VALUES ($1, $2, $3, $4)
+        ON CONFLICT
+            DO NOTHING
+        ",
+        follower_id,
+        follow.kind.as_str(),
+        follow.target,
+        &follow.breadcrumbs,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[server(RemoveFollow, "/api", "Cbor")]
+pub async fn remove_follow(follow: Follow) -> Result<(), ServerFnError> {
+    let Some(follower_id) = follower_id() else {
+        return Ok(());
+    };
+    let pool = crate::state::pool()?;
+    unfollow_as(&pool, follower_id, &follow)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+#[cfg(feature = "ssr")]
+pub async fn unfollow_as(
+    pool: &sqlx::PgPool,
+    follower_id: uuid::Uuid,
+    follow: &Follow,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r"--sql
+        DELETE FROM follows
+        WHERE follower_id = $1
+            AND kind = $2
+            AND target = $3
+            AND breadcrumbs = $4
+        ",
+        follower_id,
+        follow.kind.as_str(),
+        follow.target,
+        &follow.breadcrumbs,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[server(GetFeed, "/api")]
+pub async fn get_feed() -> Result<Vec<FeedItem>, ServerFnError> {
+    let Some(follower_id) = follower_id() else {
+        return Ok(vec![]);
+    };
+    let pool = crate::state::pool()?;
+    feed(&pool, follower_id)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+/// The latest changes to the followed lists, categories and projects,
+/// newest first. Removals are left out, the feed is about what's new.
+///
+/// Starts from the few followed links rather than checking every event
+/// against the follows.
+#[cfg(feature = "ssr")]
+pub async fn feed(
+    pool: &sqlx::PgPool,
+    follower_id: uuid::Uuid,
+) -> Result<Vec<FeedItem>, sqlx::Error> {
+    let items = sqlx::query!(
+        r#"--sql
+        WITH followed AS (
+            SELECT
+                l.id
+            FROM
+                follows f
+                JOIN awesome_lists al ON lower(al.owner) = lower(split_part(f.target, '/', 1))
+                    AND lower(al.repo) = lower(split_part(f.target, '/', 2))
+                JOIN awesome_links l ON l.awesome_list_id = al.id
+            WHERE
+                f.follower_id = $1
+                AND f.kind IN ('list', 'category')
+                AND l.breadcrumbs[1:cardinality(f.breadcrumbs)] = f.breadcrumbs
+            UNION
+            SELECT
+                l.id
+            FROM
+                follows f
+                JOIN awesome_links l ON lower(rtrim(l.url, '/')) = lower(rtrim(f.target, '/'))
+            WHERE
+                f.follower_id = $1
+                AND f.kind = 'project'
+        )
+        SELECT
+            e.kind,
+            e.occurred_at,
+            l.url,
+            l.title,
+            l.description,
+            l.link_type,
+            l.breadcrumbs AS "breadcrumbs!",
+            e.old_breadcrumbs,
+            al.owner,
+            al.repo
+        FROM
+            followed
+            JOIN awesome_link_events e ON e.awesome_link_id = followed.id
+            JOIN awesome_links l ON l.id = e.awesome_link_id
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+        WHERE
+            e.kind <> 'removed'
+        ORDER BY
+            e.occurred_at DESC
+        LIMIT 100
+        "#,
+        follower_id,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items
+        .into_iter()
+        .map(|i| FeedItem {
+            kind: i.kind,
+            occurred_at: i.occurred_at,
+            url: i.url,
+            title: i.title.unwrap_or_default(),
+            description: i.description,
+            link_type: i.link_type,
+            list: format!(
+                "{}/{}",
+                i.owner.unwrap_or_default(),
+                i.repo.unwrap_or_default()
+            ),
+            breadcrumbs: i.breadcrumbs,
+            old_breadcrumbs: i.old_breadcrumbs,
+        })
+        .collect())
+}
+
+/// The follows of this browser, shared by every follow button and the feed
+/// so they load once and stay in sync.
+#[derive(Clone, Copy)]
+pub struct Follows {
+    add: Action<AddFollow, Result<(), ServerFnError>>,
+    remove: Action<RemoveFollow, Result<(), ServerFnError>>,
+    follows: Resource<(usize, usize), Result<Vec<Follow>, ServerFnError>>,
+}
+
+impl Follows {
+    /// Makes them available to every page.
+    pub fn provide() {
+        let add = create_server_action::<AddFollow>();
+        let remove = create_server_action::<RemoveFollow>();
+        let follows = create_resource(
+            move || (add.version().get(), remove.version().get()),
+            |_| get_follows(),
+        );
+        provide_context(Follows {
+            add,
+            remove,
+            follows,
+        });
+    }
+
+    /// `None` until they are loaded.
+    fn contains(&self, follow: &Follow) -> Option<bool> {
+        self.follows.with(|follows| {
+            follows
+                .as_ref()
+                .map(|follows| follows.as_ref().is_ok_and(|f| f.contains(follow)))
+        })
+    }
+
+    fn pending(&self) -> bool {
+        self.add.pending().get() || self.remove.pending().get()
+    }
+}
+
+/// Follows a list, a category of a list or a project, or unfollows it
+/// when it already is.
+#[component]
+pub fn FollowButton(
+    kind: FollowKind,
+    #[prop(into)] target: String,
+    #[prop(optional)] breadcrumbs: Vec<String>,
+) -> impl IntoView {
+    let follows = expect_context::<Follows>();
+    let follow = store_value(Follow {
+        kind,
+        target,
+        breadcrumbs,
+    });
+    let following = move || follow.with_value(|f| follows.contains(f));
+
+    view! {
+        <button
+            class="btn btn-xs btn-outline"
+            disabled=move || following().is_none() || follows.pending()
+            on:click=move |ev| {
+                // the button also sits in `<summary>`s
+                ev.prevent_default();
+                let follow = follow.get_value();
+                if following() == Some(true) {
+                    follows.remove.dispatch(RemoveFollow { follow });
+                } else {
+                    follows.add.dispatch(AddFollow { follow });
+                }
+            }
+        >
+            {move || if following() == Some(true) { "Unfollow" } else { "Follow" }}
+        </button>
+    }
+}
+
+/// What changed lately in everything followed from this browser.
+#[component]
+pub fn Feed() -> impl IntoView {
+    let Follows {
+        remove, follows, ..
+    } = expect_context::<Follows>();
+    let feed = create_resource(move || remove.version().get(), |_| get_feed());
+
+    view! {
+        <Title text="Feed"/>
+        <h1 class="text-2xl">"Feed"</h1>
+        <Transition fallback=move || view! { <p>"Loading..."</p> }>
+            <ErrorBoundary fallback=|errors| view! { <ServerErrors errors/> }>
+                {move || {
+                    follows
+                        .get()
+                        .map(|follows| {
+                            follows
+                                .map(|follows| {
+                                    if follows.is_empty() {
+                                        return view! {
+                                            <p class="text-gray-400">
+                                                "Follow lists, categories or projects to see their new links here."
+                                            </p>
+                                        }
+                                            .into_view();
+                                    }
+                                    view! {
+                                        <div class="flex flex-wrap gap-1 items-center">
+                                            <span class="text-sm text-gray-400">"Following"</span>
+                                            {follows
+                                                .into_iter()
+                                                .map(|follow| view! { <FollowChip follow remove/> })
+                                                .collect_view()}
+                                        </div>
+                                    }
+                                        .into_view()
+                                })
+                        })
+                }}
+                {move || {
+                    feed.get().map(|feed| feed.map(|feed| view! { <FeedItems feed/> }))
+                }}
+            </ErrorBoundary>
+        </Transition>
+    }
+}
+
+#[component]
+fn FollowChip(
+    follow: Follow,
+    remove: Action<RemoveFollow, Result<(), ServerFnError>>,
+) -> impl IntoView {
+    let (href, label) = match follow.kind {
+        FollowKind::List => (format!("/lists/{}", follow.target), follow.target.clone()),
+        FollowKind::Category => (
+            format!("/lists/{}", follow.target),
+            format!("{} › {}", follow.target, follow.breadcrumbs.join(" › ")),
+        ),
+        FollowKind::Project => (
+            project_path(&follow.target).unwrap_or_else(|| follow.target.clone()),
+            follow.target.clone(),
+        ),
+    };
+
+    view! {
+        <span class="badge badge-outline">
+            <A href=href class="hover:underline">{label}</A>
+            <button
+                class="ml-1"
+                title="Unfollow"
+                on:click=move |_| remove.dispatch(RemoveFollow { follow: follow.clone() })
+            >
+                "✕"
+            </button>
+        </span>
+    }
+}
+
+#[component]
+fn FeedItems(feed: Vec<FeedItem>) -> impl IntoView {
+    view! {
+        <ul class="flex flex-col gap-2">
+            {feed
+                .into_iter()
+                .map(|item| {
+                    let change = match item.kind.as_str() {
+                        "added" => "added".to_string(),
+                        "moved" => {
+                            format!(
+                                "moved from {}",
+                                item.old_breadcrumbs.unwrap_or_default().join(" › "),
+                            )
+                        }
+                        _ => "new description".to_string(),
+                    };
+                    view! {
+                        <li>
+                            <span title=item.link_type.clone()>{link_type_icon(item.link_type.as_deref())}</span>
+                            " "
+                            <a href=item.url.clone() class="font-bold hover:underline">
+                                {item.title}
+                            </a>
+                            <span class="ml-2 text-xs text-gray-400">
+                                {change} " " {item.occurred_at.format("%Y-%m-%d").to_string()}
+                            </span>
+                            <div class="text-sm text-gray-400">
+                                <A href=format!("/lists/{}", item.list) class="hover:underline">
+                                    {item.list.clone()}
+                                </A>
+                                " › "
+                                {item.breadcrumbs.join(" › ")}
+                            </div>
+                            {item.description.map(|d| view! { <p>{d}</p> })}
+                        </li>
+                    }
+                })
+                .collect_view()}
+        </ul>
+    }
+}
@@ -0,0 +1,579 @@
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    http::GraphiQLSource,
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Schema, ServerError,
+    SimpleObject, Value,
+};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Deep enough for lists → links → project → lists → links.
+const MAX_DEPTH: usize = 12;
+/// Fields returning many objects count once per object asked for, so
+/// nesting them multiplies.
+const MAX_COMPLEXITY: usize = 5000;
+const MAX_LISTS: i32 = 100;
+const MAX_LINKS: i32 = 500;
+
+pub type EpicListSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+pub fn schema(pool: PgPool) -> EpicListSchema {
+    schema_with_stats(pool, LoaderStats::default())
+}
+
+/// The schema, counting the queries of its loaders in `stats`.
+pub fn schema_with_stats(pool: PgPool, stats: LoaderStats) -> EpicListSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(DataLoader::new(
+            PgLoader {
+                pool: pool.clone(),
+                stats,
+            },
+            tokio::spawn,
+        ))
+        .data(pool)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// The only documents clients may run, keyed by the SHA-256 of their text
+/// as in Apollo's persisted queries. Without it any query is accepted.
+#[derive(Debug, Clone, Default)]
+pub struct PersistedQueries {
+    queries: HashMap<String, String>,
+}
+
+impl PersistedQueries {
+    /// Every `.graphql` file in `dir` becomes one allowed document.
+    pub fn load(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut queries = HashMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "graphql") {
+                let query = std::fs::read_to_string(&path)?;
+                queries.insert(hash(&query), query);
+            }
+        }
+        Ok(PersistedQueries { queries })
+    }
+
+    pub fn insert(&mut self, query: impl Into<String>) -> String {
+        let query = query.into();
+        let hash = hash(&query);
+        self.queries.insert(hash.clone(), query);
+        hash
+    }
+
+    /// Fills in the document of a request sending only its hash, and turns
+    /// away documents that aren't persisted.
+    fn resolve(&self, request: &mut async_graphql::Request) -> Result<(), String> {
+        let sent_hash = match request.extensions.get("persistedQuery") {
+            Some(Value::Object(persisted)) => match persisted.get("sha256Hash") {
+                Some(Value::String(hash)) => Some(hash.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let hash = sent_hash.unwrap_or_else(|| hash(&request.query));
+        match self.queries.get(&hash) {
+            Some(query) => {
+                request.query = query.clone();
+                Ok(())
+            }
+            None if request.query.is_empty() => Err("PersistedQueryNotFound".to_string()),
+            None => Err("only persisted queries are allowed".to_string()),
+        }
+    }
+}
+
+fn hash(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+#[derive(Clone)]
+pub struct GraphqlState {
+    pub schema: EpicListSchema,
+    pub persisted_queries: Option<Arc<PersistedQueries>>,
+}
+
+/// `POST /graphql`, and GraphiQL at `GET /graphql` while any query goes.
+pub fn router() -> Router<GraphqlState> {
+    Router::new().route("/", get(graphiql).post(graphql))
+}
+
+async fn graphql(State(state): State<GraphqlState>, request: GraphQLRequest) -> GraphQLResponse {
+    let mut request = request.into_inner();
+    if let Some(persisted_queries) = &state.persisted_queries {
+        if let Err(message) = persisted_queries.resolve(&mut request) {
+            return async_graphql::Response::from_errors(vec![ServerError::new(message, None)])
+                .into();
+        }
+    }
+    state.schema.execute(request).await.into()
+}
+
+async fn graphiql(State(state): State<GraphqlState>) -> impl IntoResponse {
+    match state.persisted_queries {
+        Some(_) => axum::http::StatusCode::NOT_FOUND.into_response(),
+        None => Html(GraphiQLSource::build().endpoint("/graphql").finish()).into_response(),
+    }
+}
+
+/// Links and projects are matched on their url without case or a trailing
+/// slash, like everywhere else.
+fn url_key(url: &str) -> String {
+    url.trim_end_matches('/').to_lowercase()
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct List {
+    pub id: Uuid,
+    pub owner: String,
+    pub repo: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub stars: Option<i32>,
+    pub topics: Vec<String>,
+    pub link_count: i32,
+    pub latest_commit_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl List {
+    /// The links currently in the list, in the order of its README. Pass the
+    /// `position` of the last link as `after` for the next page
+    #[graphql(complexity = "first.clamp(1, MAX_LINKS) as usize * child_complexity")]
+    async fn links(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only links in this category and below it")] category: Option<Vec<String>>,
+        after: Option<i32>,
+        #[graphql(default = 50)] first: i32,
+    ) -> async_graphql::Result<Vec<Link>> {
+        let links = loader(ctx)
+            .load_one(LinksOf {
+                list_id: self.id,
+                category: category.unwrap_or_default(),
+                after: after.unwrap_or(0),
+                first: first.clamp(1, MAX_LINKS),
+            })
+            .await?;
+        Ok(links.unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct Link {
+    #[graphql(skip)]
+    pub list_id: Uuid,
+    /// Where the link is in its list, counting from 1
+    pub position: i32,
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub breadcrumbs: Vec<String>,
+    /// One of the `AwesomeLinkType` variants, e.g. `Repo`
+    pub link_type: Option<String>,
+}
+
+#[ComplexObject]
+impl Link {
+    async fn list(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<List>> {
+        Ok(loader(ctx).load_one(ListId(self.list_id)).await?)
+    }
+
+    /// The linked repository, when it was enriched from GitHub
+    async fn project(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Project>> {
+        Ok(loader(ctx).load_one(ProjectUrl(url_key(&self.url))).await?)
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct Project {
+    pub url: String,
+    pub name_with_owner: String,
+    pub description: Option<String>,
+    pub stars: i32,
+    pub forks: i32,
+    pub primary_language: Option<String>,
+    pub license: Option<String>,
+    pub topics: Vec<String>,
+    pub is_archived: bool,
+    pub pushed_at: Option<DateTime<Utc>>,
+}
+
+#[ComplexObject]
+impl Project {
+    /// Every list linking to the project
+    async fn lists(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<List>> {
+        Ok(loader(ctx)
+            .load_one(ListsLinking(url_key(&self.url)))
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Awesome lists ordered by id, pass the last id as `after` for the
+    /// next page
+    #[graphql(complexity = "first.clamp(1, MAX_LISTS) as usize * child_complexity")]
+    async fn lists(
+        &self,
+        ctx: &Context<'_>,
+        topic: Option<String>,
+        after: Option<Uuid>,
+        #[graphql(default = 50)] first: i32,
+    ) -> async_graphql::Result<Vec<List>> {
+        let pool = ctx.data::<PgPool>()?;
+        let lists = sqlx::query_as!(
+            List,
+            r#"--sql
+            SELECT
+                id,
+                coalesce(owner, '') AS "owner!",
+                coalesce(repo, '') AS "repo!",
+                url,
+                description,
+                stars,
+                topics,
+                link_count,
+                latest_commit_at
+            FROM
+                awesome_lists
+            WHERE
+                owner IS NOT NULL
+                AND ($1::uuid IS NULL OR id > $1)
+                AND ($2::text IS NULL OR $2 = ANY (topics))
+            ORDER BY
+                id
+            LIMIT $3
+            "#,
+            after,
+            topic,
+            i64::from(first.clamp(1, MAX_LISTS)),
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(lists)
+    }
+
+    async fn list(
+        &self,
+        ctx: &Context<'_>,
+        owner: String,
+        repo: String,
+    ) -> async_graphql::Result<Option<List>> {
+        let pool = ctx.data::<PgPool>()?;
+        let list = sqlx::query_as!(
+            List,
+            r#"--sql
+            SELECT
+                id,
+                coalesce(owner, '') AS "owner!",
+                coalesce(repo, '') AS "repo!",
+                url,
+                description,
+                stars,
+                topics,
+                link_count,
+                latest_commit_at
+            FROM
+                awesome_lists
+            WHERE
+                lower(owner) = lower($1)
+                AND lower(repo) = lower($2)
+            "#,
+            owner,
+            repo,
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(list)
+    }
+
+    /// A project by the url of its repository
+    async fn project(
+        &self,
+        ctx: &Context<'_>,
+        url: String,
+    ) -> async_graphql::Result<Option<Project>> {
+        Ok(loader(ctx).load_one(ProjectUrl(url_key(&url))).await?)
+    }
+}
+
+fn loader<'a>(ctx: &Context<'a>) -> &'a DataLoader<PgLoader> {
+    ctx.data_unchecked::<DataLoader<PgLoader>>()
+}
+
+/// Batches the lookups of all objects resolved at the same depth into one
+/// query per kind.
+pub struct PgLoader {
+    pool: PgPool,
+    stats: LoaderStats,
+}
+
+/// How many queries the loaders ran, to tell whether lookups get batched.
+#[derive(Debug, Clone, Default)]
+pub struct LoaderStats(Arc<AtomicUsize>);
+
+impl LoaderStats {
+    pub fn queries(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn count(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ListId(Uuid);
+
+/// One page of a list's links, `category` empty for all of them
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LinksOf {
+    list_id: Uuid,
+    category: Vec<String>,
+    after: i32,
+    first: i32,
+}
+
+/// See `url_key`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ProjectUrl(String);
+
+/// See `url_key`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ListsLinking(String);
+
+#[async_trait::async_trait]
+impl Loader<ListId> for PgLoader {
+    type Value = List;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[ListId]) -> Result<HashMap<ListId, List>, Self::Error> {
+        self.stats.count();
+        let ids = keys.iter().map(|k| k.0).collect::<Vec<_>>();
+        let lists = sqlx::query_as!(
+            List,
+            r#"--sql
+            SELECT
+                id,
+                coalesce(owner, '') AS "owner!",
+                coalesce(repo, '') AS "repo!",
+                url,
+                description,
+                stars,
+                topics,
+                link_count,
+                latest_commit_at
+            FROM
+                awesome_lists
+            WHERE
+                id = ANY ($1)
+            "#,
+            &ids,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(lists.into_iter().map(|l| (ListId(l.id), l)).collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<LinksOf> for PgLoader {
+    type Value = Vec<Link>;
+    type Error = Arc<sqlx::Error>;
+
+    /// One query per category asked for, the pages of all lists in it at once.
+    /// Positions count every link of a list, so a page within a category
+    /// still continues after the `position` of its last link.
+    async fn load(&self, keys: &[LinksOf]) -> Result<HashMap<LinksOf, Vec<Link>>, Self::Error> {
+        let mut by_category = HashMap::<&[String], Vec<&LinksOf>>::new();
+        for key in keys {
+            by_category.entry(&key.category).or_default().push(key);
+        }
+
+        let mut links = HashMap::<LinksOf, Vec<Link>>::new();
+        for (category, keys) in by_category {
+            self.stats.count();
+            let ids = keys.iter().map(|k| k.list_id).collect::<Vec<_>>();
+            let after = keys.iter().map(|k| k.after).collect::<Vec<_>>();
+            let first = keys.iter().map(|k| k.first as i64).collect::<Vec<_>>();
+            let rows = sqlx::query!(
+                r#"--sql
+                SELECT
+                    k.key AS "key!",
+                    l.awesome_list_id AS "list_id!",
+                    l.position AS "position!",
+                    l.url AS "url!",
+                    l.title,
+                    l.description,
+                    l.breadcrumbs AS "breadcrumbs!",
+                    l.link_type
+                FROM
+                    unnest($1::uuid[], $2::integer[], $3::bigint[])
+                        WITH ORDINALITY AS k(list_id, after, first, key)
+                    CROSS JOIN LATERAL (
+                        SELECT
+                            *
+                        FROM (
+                            SELECT
+                                awesome_list_id,
+                                row_number() OVER (ORDER BY source_line, id)::integer AS position,
+                                url,
+                                title,
+                                description,
+                                breadcrumbs,
+                                link_type
+                            FROM
+                                current_awesome_links
+                            WHERE
+                                awesome_list_id = k.list_id
+                        ) positioned
+                        WHERE
+                            position > k.after
+                            AND breadcrumbs[1:cardinality($4::text[])] = $4
+                        ORDER BY
+                            position
+                        LIMIT k.first
+                    ) l
+                ORDER BY
+                    k.key,
+                    l.position
+                "#,
+                &ids,
+                &after,
+                &first,
+                category,
+            )
+            .fetch_all(&self.pool)
+            .await?;
+
+            for row in rows {
+                let key = keys[row.key as usize - 1];
+                links.entry(key.clone()).or_default().push(Link {
+                    list_id: row.list_id,
+                    position: row.position,
+                    url: row.url,
+                    title: row.title,
+                    description: row.description,
+                    breadcrumbs: row.breadcrumbs,
+                    link_type: row.link_type,
+                });
+            }
+        }
+        Ok(links)
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<ProjectUrl> for PgLoader {
+    type Value = Project;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[ProjectUrl]) -> Result<HashMap<ProjectUrl, Project>, Self::Error> {
+        self.stats.count();
+        let urls = keys.iter().map(|k| k.0.clone()).collect::<Vec<_>>();
+        let projects = sqlx::query_as!(
+            Project,
+            r"--sql
+            SELECT
+                url,
+                name_with_owner,
+                description,
+                stars,
+                forks,
+                primary_language,
+                license,
+                topics,
+                is_archived,
+                pushed_at
+            FROM
+                github_projects
+            WHERE
+                lower(url) = ANY ($1)
+            ",
+            &urls,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(projects
+            .into_iter()
+            .map(|p| (ProjectUrl(url_key(&p.url)), p))
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<ListsLinking> for PgLoader {
+    type Value = Vec<List>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(
+        &self,
+        keys: &[ListsLinking],
+    ) -> Result<HashMap<ListsLinking, Vec<List>>, Self::Error> {
+        self.stats.count();
+        let urls = keys.iter().map(|k| k.0.clone()).collect::<Vec<_>>();
+        let rows = sqlx::query!(
+            r#"--sql
+            SELECT DISTINCT
+                lower(rtrim(l.url, '/')) AS "key!",
+                al.id AS "id!",
+                coalesce(al.owner, '') AS "owner!",
+                coalesce(al.repo, '') AS "repo!",
+                al.url AS "url!",
+                al.description,
+                al.stars,
+                al.topics AS "topics!",
+                al.link_count AS "link_count!",
+                al.latest_commit_at AS "latest_commit_at!"
+            FROM
+                current_awesome_links l
+                JOIN awesome_lists al ON al.id = l.awesome_list_id
+            WHERE
+                lower(rtrim(l.url, '/')) = ANY ($1)
+            "#,
+            &urls,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut lists = HashMap::<ListsLinking, Vec<List>>::new();
+        for r in rows {
+            lists.entry(ListsLinking(r.key)).or_default().push(List {
+                id: r.id,
+                owner: r.owner,
+                repo: r.repo,
+                url: r.url,
+                description: r.description,
+                stars: r.stars,
+                topics: r.topics,
+                link_count: r.link_count,
+                latest_commit_at: r.latest_commit_at,
+            });
+        }
+        Ok(lists)
+    }
+}
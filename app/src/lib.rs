@@ -1,7 +1,24 @@
 use cfg_if::cfg_if;
 pub mod app;
+#[cfg(feature = "ssr")]
+pub mod badge;
+pub mod components;
+pub mod epiclist;
+pub mod equivalents;
 pub mod error_template;
+pub mod feed;
 pub mod fileserv;
+#[cfg(feature = "ssr")]
+pub mod graphql;
+pub mod lists;
+pub mod projects;
+#[cfg(feature = "ssr")]
+pub mod rest;
+pub mod search;
+pub mod state;
+#[cfg(feature = "ssr")]
+pub mod syndication;
+pub mod tree;
 
 cfg_if! { if #[cfg(feature = "hydrate")] {
     use leptos::*;
@@ -0,0 +1,583 @@
+use crate::components::{link_type_icon, NotFound, ServerErrors};
+use crate::feed::{FollowButton, FollowKind};
+use crate::projects::project_path;
+use crate::tree::{category_tree, Category};
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+const PER_PAGE: i64 = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ListSort {
+    #[default]
+    Stars,
+    Links,
+    Updated,
+    Name,
+}
+
+impl ListSort {
+    const ALL: [ListSort; 4] = [
+        ListSort::Stars,
+        ListSort::Links,
+        ListSort::Updated,
+        ListSort::Name,
+    ];
+
+    /// The value used in query strings and by the server query.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListSort::Stars => "stars",
+            ListSort::Links => "links",
+            ListSort::Updated => "updated",
+            ListSort::Name => "name",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ListSort::Stars => "Most stars",
+            ListSort::Links => "Most links",
+            ListSort::Updated => "Recently updated",
+            ListSort::Name => "Name",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str() == value)
+    }
+}
+
+/// An awesome list as shown in the overview.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListSummary {
+    pub owner: String,
+    pub repo: String,
+    pub description: Option<String>,
+    pub stars: Option<i32>,
+    pub link_count: i32,
+    pub topics: Vec<String>,
+    pub latest_commit_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListsPage {
+    pub lists: Vec<ListSummary>,
+    /// Matching lists across all pages
+    pub total: i64,
+}
+
+#[server(GetLists, "/api")]
+pub async fn get_lists(
+    filter: Option<String>,
+    topic: Option<String>,
+    sort: ListSort,
+    page: i64,
+) -> Result<ListsPage, ServerFnError> {
+    let pool = crate::state::pool()?;
+
+    let filter = filter.filter(|f| !f.is_empty());
+
+    // counted apart from the page, which is empty past the last one
+    let total = sqlx::query_scalar!(
+        r#"--sql
+        SELECT
+            count(*) AS "total!"
+        FROM
+            awesome_lists
+        WHERE
+            owner IS NOT NULL
+            AND ($1::text IS NULL
+                OR strpos(lower(owner || '/' || repo), lower($1)) > 0
+                OR strpos(lower(description), lower($1)) > 0)
+            AND ($2::text IS NULL OR $2 = ANY (topics))
+        "#,
+        filter,
+        topic,
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+    let rows = sqlx::query!(
+        r#"--sql
+        SELECT
+            owner,
+            repo,
+            description,
+            stars,
+            link_count,
+            topics,
+            latest_commit_at
+        FROM
+            awesome_lists
+        WHERE
+            owner IS NOT NULL
+            AND ($1::text IS NULL
+                OR strpos(lower(owner || '/' || repo), lower($1)) > 0
+                OR strpos(lower(description), lower($1)) > 0)
+            AND ($2::text IS NULL OR $2 = ANY (topics))
+        ORDER BY
+            CASE WHEN $3 = 'stars' THEN stars END DESC NULLS LAST,
+            CASE WHEN $3 = 'links' THEN link_count END DESC,
+            CASE WHEN $3 = 'updated' THEN latest_commit_at END DESC,
+            lower(owner || '/' || repo)
+        LIMIT $4 OFFSET $5
+        "#,
+        filter,
+        topic,
+        sort.as_str(),
+        PER_PAGE,
+        page.max(0) * PER_PAGE,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+    Ok(ListsPage {
+        total,
+        lists: rows
+            .into_iter()
+            .map(|r| ListSummary {
+                owner: r.owner.unwrap_or_default(),
+                repo: r.repo.unwrap_or_default(),
+                description: r.description,
+                stars: r.stars,
+                link_count: r.link_count,
+                topics: r.topics,
+                latest_commit_at: r.latest_commit_at,
+            })
+            .collect(),
+    })
+}
+
+/// Every ingested awesome list, filterable by name, description and topic.
+/// All state lives in the query string so filtered views can be shared.
+#[component]
+pub fn Lists() -> impl IntoView {
+    let query = use_query_map();
+    let param = move |key: &str| query.with(|q| q.get(key).cloned());
+    let sort = move || {
+        param("sort")
+            .and_then(|s| ListSort::parse(&s))
+            .unwrap_or_default()
+    };
+    let page = move || {
+        param("page")
+            .and_then(|p| p.parse::<i64>().ok())
+            .unwrap_or(0)
+    };
+
+    let lists = create_resource(
+        move || (param("q"), param("topic"), sort(), page()),
+        |(filter, topic, sort, page)| get_lists(filter, topic, sort, page),
+    );
+
+    // keeps the other parameters when paging or picking a topic
+    let href = move |key: &str, value: String| {
+        let mut params = query.get();
+        params.insert(key.to_string(), value);
+        if key != "page" {
+            params.remove("page");
+        }
+        format!("/lists{}", params.to_query_string())
+    };
+
+    view! {
+        <Title text="Awesome lists"/>
+        <h1 class="text-2xl">"All awesome lists"</h1>
+        <Form method="GET" action="/lists" class="flex flex-wrap gap-2">
+            <input
+                type="search"
+                name="q"
+                placeholder="Filter by name or description"
+                class="text-black input input-bordered"
+                prop:value=move || param("q").unwrap_or_default()
+            />
+            {move || {
+                param("topic").map(|topic| view! { <input type="hidden" name="topic" value=topic/> })
+            }}
+            <select name="sort" class="text-black select select-bordered">
+                {ListSort::ALL
+                    .into_iter()
+                    .map(|s| {
+                        view! {
+                            <option value=s.as_str() selected=move || sort() == s>
+                                {s.label()}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+            </select>
+            <button type="submit" class="btn">"Filter"</button>
+        </Form>
+        <Transition fallback=move || view! { <p>"Loading..."</p> }>
+            <ErrorBoundary fallback=|errors| view! { <ServerErrors errors/> }>
+                {move || {
+                    lists
+                        .get()
+                        .map(|lists| {
+                            lists
+                                .map(|lists| {
+                                    let pages = (lists.total + PER_PAGE - 1) / PER_PAGE;
+                                    view! {
+                                        <p class="text-sm text-gray-400">{lists.total} " lists"</p>
+                                        <ListsTable lists=lists.lists topic_href=move |t| href("topic", t)/>
+                                        <div class="join">
+                                            <Show when=move || { page() > 0 } fallback=|| ()>
+                                                <A href=move || href("page", (page() - 1).to_string()) class="join-item btn">
+                                                    "«"
+                                                </A>
+                                            </Show>
+                                            <span class="join-item btn btn-disabled">
+                                                {move || page() + 1} " / " {pages.max(1)}
+                                            </span>
+                                            <Show when=move || { page() + 1 < pages } fallback=|| ()>
+                                                <A href=move || href("page", (page() + 1).to_string()) class="join-item btn">
+                                                    "»"
+                                                </A>
+                                            </Show>
+                                        </div>
+                                    }
+                                })
+                        })
+                }}
+            </ErrorBoundary>
+        </Transition>
+    }
+}
+
+#[component]
+fn ListsTable<F>(lists: Vec<ListSummary>, topic_href: F) -> impl IntoView
+where
+    F: Fn(String) -> String + Copy + 'static,
+{
+    view! {
+        <table class="table">
+            <thead>
+                <tr class="text-gray-400">
+                    <th>"List"</th>
+                    <th>"Stars"</th>
+                    <th>"Links"</th>
+                    <th>"Last commit"</th>
+                </tr>
+            </thead>
+            <tbody>
+                {lists
+                    .into_iter()
+                    .map(|list| {
+                        view! {
+                            <tr>
+                                <td>
+                                    <A href=format!("/lists/{}/{}", list.owner, list.repo) class="font-bold hover:underline">
+                                        {format!("{}/{}", list.owner, list.repo)}
+                                    </A>
+                                    {list.description.map(|d| view! { <p class="text-sm">{d}</p> })}
+                                    <div class="flex flex-wrap gap-1 mt-1">
+                                        {list
+                                            .topics
+                                            .into_iter()
+                                            .map(|topic| {
+                                                view! {
+                                                    <A href=topic_href(topic.clone()) class="badge badge-outline">
+                                                        {topic}
+                                                    </A>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                </td>
+                                <td>{list.stars}</td>
+                                <td>{list.link_count}</td>
+                                <td>{list.latest_commit_at.format("%Y-%m-%d").to_string()}</td>
+                            </tr>
+                        }
+                    })
+                    .collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+/// A link as shown on its list's page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListLink {
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub breadcrumbs: Vec<String>,
+    pub link_type: Option<String>,
+    /// The project is archived or its description says it's unmaintained
+    pub deprecated: bool,
+    pub added_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListDetails {
+    pub list: ListSummary,
+    pub links: Vec<ListLink>,
+}
+
+#[server(GetList, "/api")]
+pub async fn get_list(owner: String, repo: String) -> Result<Option<ListDetails>, ServerFnError> {
+    let pool = crate::state::pool()?;
+
+    let Some(list) = sqlx::query!(
+        r"--sql
+        SELECT
+            id,
+            owner,
+            repo,
+            description,
+            stars,
+            link_count,
+            topics,
+            latest_commit_at
+        FROM
+            awesome_lists
+        WHERE
+            lower(owner) = lower($1)
+            AND lower(repo) = lower($2)
+        ",
+        owner,
+        repo,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| ServerFnError::ServerError(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    let links = sqlx::query!(
+        r#"--sql
+        SELECT
+            l.url AS "url!",
+            l.title,
+            l.description,
+            l.breadcrumbs AS "breadcrumbs!",
+            l.link_type,
+            coalesce(gp.is_archived, FALSE)
+                OR coalesce(l.description ~* '\m(deprecated|unmaintained|no longer maintained)\M', FALSE) AS "deprecated!",
+            (
+                SELECT
+                    max(e.occurred_at)
+                FROM
+                    awesome_link_events e
+                WHERE
+                    e.awesome_link_id = l.id
+                    AND e.kind = 'added') AS added_at
+        FROM
+            current_awesome_links l
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+        WHERE
+            l.awesome_list_id = $1
+        ORDER BY
+            l.source_line,
+            l.id
+        "#,
+        list.id,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+    Ok(Some(ListDetails {
+        list: ListSummary {
+            owner: list.owner.unwrap_or(owner),
+            repo: list.repo.unwrap_or(repo),
+            description: list.description,
+            stars: list.stars,
+            link_count: list.link_count,
+            topics: list.topics,
+            latest_commit_at: list.latest_commit_at,
+        },
+        links: links
+            .into_iter()
+            .map(|l| ListLink {
+                url: l.url,
+                title: l.title.unwrap_or_default(),
+                description: l.description,
+                breadcrumbs: l.breadcrumbs,
+                link_type: l.link_type,
+                deprecated: l.deprecated,
+                added_at: l.added_at,
+            })
+            .collect(),
+    }))
+}
+
+#[derive(Params, PartialEq, Clone)]
+struct ListParams {
+    owner: Option<String>,
+    repo: Option<String>,
+}
+
+/// One list with its links in the categories of its README. Rendered
+/// completely on the server, hydration only adds expanding and collapsing
+/// everything at once.
+#[component]
+pub fn ListPage() -> impl IntoView {
+    let params = use_params::<ListParams>();
+    let details = create_resource(
+        move || {
+            params.with(|p| {
+                let p = p.as_ref().ok()?;
+                Some((p.owner.clone()?, p.repo.clone()?))
+            })
+        },
+        |list| async move {
+            match list {
+                Some((owner, repo)) => get_list(owner, repo).await,
+                None => Ok(None),
+            }
+        },
+    );
+    let (expanded, set_expanded) = create_signal(true);
+
+    view! {
+        <Suspense fallback=move || view! { <p>"Loading..."</p> }>
+            <ErrorBoundary fallback=|errors| view! { <ServerErrors errors/> }>
+                {move || {
+                    details
+                        .get()
+                        .map(|details| {
+                            details
+                                .map(|details| match details {
+                                    None => view! { <NotFound what="List"/> }.into_view(),
+                                    Some(ListDetails { list, links }) => {
+                                        let name = format!("{}/{}", list.owner, list.repo);
+                                        let tree = category_tree(
+                                            links.into_iter().map(|l| (l.breadcrumbs.clone(), l)),
+                                        );
+                                        view! {
+                                            <Title text=name.clone()/>
+                                            <h1 class="text-2xl">
+                                                <a href=format!("https://github.com/{name}") class="hover:underline">
+                                                    {name.clone()}
+                                                </a>
+                                                <span class="ml-2">
+                                                    <FollowButton kind=FollowKind::List target=name.clone()/>
+                                                </span>
+                                            </h1>
+                                            {list.description.map(|d| view! { <p>{d}</p> })}
+                                            <p class="text-sm text-gray-400">
+                                                {list.stars.map(|s| format!("★ {s} · "))}
+                                                {list.link_count} " links · last commit "
+                                                {list.latest_commit_at.format("%Y-%m-%d").to_string()}
+                                                " · "
+                                                <a href=format!("/lists/{name}/feed.atom") rel="external" class="hover:underline">
+                                                    "Atom"
+                                                </a>
+                                                " · "
+                                                <a href=format!("/lists/{name}/feed.rss") rel="external" class="hover:underline">
+                                                    "RSS"
+                                                </a>
+                                            </p>
+                                            <div class="flex gap-2">
+                                                <button class="btn btn-sm" on:click=move |_| set_expanded.set(true)>
+                                                    "Expand all"
+                                                </button>
+                                                <button class="btn btn-sm" on:click=move |_| set_expanded.set(false)>
+                                                    "Collapse all"
+                                                </button>
+                                            </div>
+                                            {category_view(tree, expanded, &name, vec![])}
+                                        }
+                                            .into_view()
+                                    }
+                                })
+                        })
+                }}
+            </ErrorBoundary>
+        </Suspense>
+    }
+}
+
+/// Nested `<details>` so the tree also folds without JavaScript. `path` are
+/// the breadcrumbs of the parent category, to follow this one.
+fn category_view(
+    category: Category<ListLink>,
+    expanded: ReadSignal<bool>,
+    list: &str,
+    mut path: Vec<String>,
+) -> View {
+    let Category {
+        name,
+        items,
+        children,
+    } = category;
+    if !name.is_empty() {
+        path.push(name.clone());
+    }
+    let count = items.len() + children.iter().map(Category::len).sum::<usize>();
+    let content = view! {
+        {(!items.is_empty())
+            .then(|| view! { <ul class="ml-2">{items.into_iter().map(link_item).collect_view()}</ul> })}
+        {children
+            .into_iter()
+            .map(|c| category_view(c, expanded, list, path.clone()))
+            .collect_view()}
+    };
+
+    // the root holds the links above the first heading
+    if name.is_empty() {
+        return content.into_view();
+    }
+    view! {
+        <details class="ml-4" open=move || expanded.get()>
+            <summary class="cursor-pointer">
+                {name} <span class="text-sm text-gray-400">" (" {count} ")"</span>
+                <span class="ml-2">
+                    <FollowButton kind=FollowKind::Category target=list breadcrumbs=path/>
+                </span>
+            </summary>
+            {content}
+        </details>
+    }
+    .into_view()
+}
+
+fn link_item(link: ListLink) -> impl IntoView {
+    view! {
+        <li class="my-1">
+            <span title=link.link_type.clone()>{link_type_icon(link.link_type.as_deref())}</span>
+            " "
+            <a
+                href=link.url.clone()
+                class=if link.deprecated { "line-through hover:underline" } else { "hover:underline" }
+            >
+                {link.title}
+            </a>
+            {project_path(&link.url)
+                .map(|p| {
+                    view! {
+                        <A href=p class="ml-1 text-xs text-gray-400 hover:underline">
+                            "details"
+                        </A>
+                    }
+                })}
+            {link
+                .deprecated
+                .then(|| {
+                    view! {
+                        <span class="ml-2 badge badge-warning" title="archived or unmaintained">
+                            "deprecated"
+                        </span>
+                    }
+                })}
+            {link.description.map(|d| view! { <span class="text-gray-300">" - " {d}</span> })}
+            {link
+                .added_at
+                .map(|added_at| {
+                    view! {
+                        <span class="ml-2 text-xs text-gray-400">
+                            "added " {added_at.format("%Y-%m-%d").to_string()}
+                        </span>
+                    }
+                })}
+        </li>
+    }
+}
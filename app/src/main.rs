@@ -3,9 +3,16 @@
 async fn main() {
     use app::app::*;
     use app::fileserv::file_and_error_handler;
-    use axum::{routing::post, Router};
+    use app::graphql::{GraphqlState, PersistedQueries};
+    use app::state::AppState;
+    use axum::{
+        routing::{get, post},
+        Router,
+    };
     use leptos::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
 
     simple_logger::SimpleLogger::new()
         .with_level(log::LevelFilter::Warn)
@@ -22,12 +29,49 @@ async fn main() {
     let addr = leptos_options.site_addr;
     let routes = generate_route_list(App);
 
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&database_url)
+        .await
+        .unwrap();
+    // without an allowlist any query goes, and GraphiQL is served
+    let persisted_queries = std::env::var("GRAPHQL_PERSISTED_QUERIES").ok().map(|dir| {
+        Arc::new(
+            PersistedQueries::load(dir)
+                .expect("GRAPHQL_PERSISTED_QUERIES must be a directory of .graphql files"),
+        )
+    });
+    let graphql_state = GraphqlState {
+        schema: app::graphql::schema(pool.clone()),
+        persisted_queries,
+    };
+
+    let app_state = AppState {
+        leptos_options,
+        pool: pool.clone(),
+    };
+
     // build our application with a route
     let app = Router::new()
-        .route("/api/*fn_name", post(leptos_axum::handle_server_fns))
-        .leptos_routes(&leptos_options, routes, App)
+        // the static `v1` segment takes precedence over the catch-all
+        .nest("/api/v1", app::rest::router().with_state(pool.clone()))
+        .route("/api/*fn_name", post(server_fn_handler))
+        .nest("/graphql", app::graphql::router().with_state(graphql_state))
+        .route("/badge/:owner/:file", get(app::badge::badge))
+        .route(
+            "/lists/:owner/:repo/:file",
+            get(app::syndication::list_feed),
+        )
+        .route("/search/:file", get(app::syndication::search_feed))
+        .leptos_routes_with_context(
+            &app_state,
+            routes,
+            move || provide_context(pool.clone()),
+            App,
+        )
         .fallback(file_and_error_handler)
-        .with_state(leptos_options);
+        .with_state(app_state);
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
@@ -38,6 +82,25 @@ async fn main() {
         .unwrap();
 }
 
+/// Server functions look up the database pool in their context.
+#[cfg(feature = "ssr")]
+async fn server_fn_handler(
+    axum::extract::State(app_state): axum::extract::State<app::state::AppState>,
+    path: axum::extract::Path<String>,
+    headers: http::HeaderMap,
+    raw_query: axum::extract::RawQuery,
+    request: http::Request<axum::body::Body>,
+) -> impl axum::response::IntoResponse {
+    leptos_axum::handle_server_fns_with_context(
+        path,
+        headers,
+        raw_query,
+        move || leptos::provide_context(app_state.pool.clone()),
+        request,
+    )
+    .await
+}
+
 #[cfg(not(feature = "ssr"))]
 pub fn main() {
     // no client-side main function
@@ -0,0 +1,537 @@
+use crate::components::{NotFound, ServerErrors};
+use crate::feed::{FollowButton, FollowKind};
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+/// Hosts of the forges a project page can be asked for, the first part of
+/// `/projects/:forge/:owner/:repo`.
+const FORGES: &[(&str, &str)] = &[
+    ("github", "github.com"),
+    ("gitlab", "gitlab.com"),
+    ("codeberg", "codeberg.org"),
+    ("bitbucket", "bitbucket.org"),
+];
+
+/// The project page of a link to a repository on a known forge.
+pub fn project_path(url: &str) -> Option<String> {
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    let path = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let path = path.strip_prefix("www.").unwrap_or(path);
+    let (host, path) = path.split_once('/')?;
+    let (forge, _) = FORGES.iter().find(|(_, h)| host.eq_ignore_ascii_case(h))?;
+    match path.split('/').collect::<Vec<_>>()[..] {
+        [owner, repo] if !owner.is_empty() && !repo.is_empty() => {
+            Some(format!("/projects/{forge}/{owner}/{repo}"))
+        }
+        _ => None,
+    }
+}
+
+/// Where a list links to the project.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ProjectListing {
+    /// As `owner/repo`
+    pub list: String,
+    pub breadcrumbs: Vec<String>,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// What the forge tells about the project, only known for GitHub.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ProjectMetrics {
+    pub description: Option<String>,
+    pub homepage_url: Option<String>,
+    pub stars: i32,
+    pub forks: i32,
+    pub primary_language: Option<String>,
+    pub license: Option<String>,
+    pub topics: Vec<String>,
+    pub is_archived: bool,
+    pub pushed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectDetails {
+    pub url: String,
+    pub listings: Vec<ProjectListing>,
+    pub metrics: Option<ProjectMetrics>,
+    /// Stars over time, oldest first
+    pub star_history: Vec<(DateTime<Utc>, i32)>,
+}
+
+/// A project listed in the same category of the same list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedProject {
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Number of categories both projects are in
+    pub shared: i64,
+    pub stars: Option<i32>,
+}
+
+fn project_url(forge: &str, owner: &str, repo: &str) -> Option<String> {
+    let (_, host) = FORGES.iter().find(|(f, _)| *f == forge)?;
+    Some(format!("https://{host}/{owner}/{repo}"))
+}
+
+#[server(GetProject, "/api")]
+pub async fn get_project(
+    forge: String,
+    owner: String,
+    repo: String,
+) -> Result<Option<ProjectDetails>, ServerFnError> {
+    let pool = crate::state::pool()?;
+    project_details(&pool, &forge, &owner, &repo)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
+
+/// Everything known about the project at `/projects/:forge/:owner/:repo`,
+/// also served by the REST API.
+#[cfg(feature = "ssr")]
+pub async fn project_details(
+    pool: &sqlx::PgPool,
+    forge: &str,
+    owner: &str,
+    repo: &str,
+) -> Result<Option<ProjectDetails>, sqlx::Error> {
+    let Some(url) = project_url(forge, owner, repo) else {
+        return Ok(None);
+    };
+
+    let listings = sqlx::query!(
+        r#"--sql
+        SELECT
+            al.owner,
+            al.repo,
+            l.title,
+            l.description,
+            l.breadcrumbs AS "breadcrumbs!"
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+        WHERE
+            lower(rtrim(l.url, '/')) = lower($1)
+        ORDER BY
+            al.stars DESC NULLS LAST,
+            al.owner,
+            al.repo
+        "#,
+        url,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let metrics = sqlx::query!(
+        r"--sql
+        SELECT
+            name_with_owner,
+            description,
+            homepage_url,
+            stars,
+            forks,
+            primary_language,
+            license,
+            topics,
+            is_archived,
+            pushed_at
+        FROM
+            github_projects
+        WHERE
+            lower(url) = lower($1)
+        ",
+        url,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if listings.is_empty() && metrics.is_none() {
+        return Ok(None);
+    }
+
+    let star_history = match &metrics {
+        Some(metrics) => sqlx::query!(
+            r"--sql
+            SELECT
+                taken_at,
+                stars
+            FROM
+                repo_snapshots
+            WHERE
+                lower(name_with_owner) = lower($1)
+            ORDER BY
+                taken_at
+            ",
+            metrics.name_with_owner,
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|s| (s.taken_at, s.stars))
+        .collect(),
+        None => vec![],
+    };
+
+    Ok(Some(ProjectDetails {
+        url,
+        listings: listings
+            .into_iter()
+            .map(|l| ProjectListing {
+                list: format!(
+                    "{}/{}",
+                    l.owner.unwrap_or_default(),
+                    l.repo.unwrap_or_default()
+                ),
+                breadcrumbs: l.breadcrumbs,
+                title: l.title.unwrap_or_default(),
+                description: l.description,
+            })
+            .collect(),
+        metrics: metrics.map(|m| ProjectMetrics {
+            description: m.description,
+            homepage_url: m.homepage_url,
+            stars: m.stars,
+            forks: m.forks,
+            primary_language: m.primary_language,
+            license: m.license,
+            topics: m.topics,
+            is_archived: m.is_archived,
+            pushed_at: m.pushed_at,
+        }),
+        star_history,
+    }))
+}
+
+/// Other projects from the categories the project is listed in, the ones
+/// sharing most categories with it first.
+#[server(GetRelatedProjects, "/api")]
+pub async fn get_related_projects(
+    forge: String,
+    owner: String,
+    repo: String,
+) -> Result<Vec<RelatedProject>, ServerFnError> {
+    let pool = crate::state::pool()?;
+    let Some(url) = project_url(&forge, &owner, &repo) else {
+        return Ok(vec![]);
+    };
+
+    let related = sqlx::query!(
+        r#"--sql
+        WITH categories AS (
+            SELECT
+                awesome_list_id,
+                breadcrumbs
+            FROM
+                current_awesome_links
+            WHERE
+                lower(rtrim(url, '/')) = lower($1)
+        )
+        SELECT
+            min(l.url) AS "url!",
+            min(l.title) AS title,
+            max(l.description) AS description,
+            count(DISTINCT (l.awesome_list_id, l.breadcrumbs)) AS "shared!",
+            max(gp.stars) AS stars
+        FROM
+            current_awesome_links l
+            JOIN categories c ON c.awesome_list_id = l.awesome_list_id
+                AND c.breadcrumbs = l.breadcrumbs
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+        WHERE
+            lower(rtrim(l.url, '/')) <> lower($1)
+        GROUP BY
+            lower(rtrim(l.url, '/'))
+        ORDER BY
+            4 DESC,
+            5 DESC NULLS LAST,
+            1
+        LIMIT 20
+        "#,
+        url,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+    Ok(related
+        .into_iter()
+        .map(|r| RelatedProject {
+            url: r.url,
+            title: r.title.unwrap_or_default(),
+            description: r.description,
+            shared: r.shared,
+            stars: r.stars,
+        })
+        .collect())
+}
+
+#[derive(Params, PartialEq, Clone)]
+struct ProjectParams {
+    forge: Option<String>,
+    owner: Option<String>,
+    repo: Option<String>,
+}
+
+impl ProjectParams {
+    fn project(&self) -> Option<(String, String, String)> {
+        Some((self.forge.clone()?, self.owner.clone()?, self.repo.clone()?))
+    }
+}
+
+/// Everything known about a linked project: the lists recommending it, what
+/// its forge says about it and similar projects from the same categories.
+#[component]
+pub fn ProjectPage() -> impl IntoView {
+    let params = use_params::<ProjectParams>();
+    let project = move || params.with(|p| p.as_ref().ok().and_then(ProjectParams::project));
+    let details = create_resource(project, |project| async move {
+        match project {
+            Some((forge, owner, repo)) => get_project(forge, owner, repo).await,
+            None => Ok(None),
+        }
+    });
+    let related = create_resource(project, |project| async move {
+        match project {
+            Some((forge, owner, repo)) => get_related_projects(forge, owner, repo).await,
+            None => Ok(vec![]),
+        }
+    });
+
+    view! {
+        <Suspense fallback=move || view! { <p>"Loading..."</p> }>
+            <ErrorBoundary fallback=|errors| view! { <ServerErrors errors/> }>
+                {move || {
+                    details
+                        .get()
+                        .map(|details| {
+                            details
+                                .map(|details| match details {
+                                    None => view! { <NotFound what="Project"/> }.into_view(),
+                                    Some(details) => {
+                                        let (forge, owner, repo) = project().unwrap_or_default();
+                                        view! {
+                                            <ProjectDetailsView details forge owner repo/>
+                                            <h2 class="text-xl">"Related projects"</h2>
+                                            {move || {
+                                                related
+                                                    .get()
+                                                    .map(|related| {
+                                                        related.map(|related| view! { <RelatedProjects related/> })
+                                                    })
+                                            }}
+                                        }
+                                            .into_view()
+                                    }
+                                })
+                        })
+                }}
+            </ErrorBoundary>
+        </Suspense>
+    }
+}
+
+#[component]
+fn ProjectDetailsView(
+    details: ProjectDetails,
+    forge: String,
+    owner: String,
+    repo: String,
+) -> impl IntoView {
+    let ProjectDetails {
+        url,
+        listings,
+        metrics,
+        star_history,
+    } = details;
+    let name = format!("{owner}/{repo}");
+    // Equivalents are looked up by GitHub URL
+    let equivalents = (forge == "github").then(|| format!("/equivalents/{owner}/{repo}"));
+    let stars = star_history.iter().map(|(_, s)| *s).collect::<Vec<_>>();
+    let history_range =
+        star_history
+            .first()
+            .zip(star_history.last())
+            .map(|((from, _), (to, _))| {
+                format!("{} – {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"))
+            });
+
+    view! {
+        <Title text=name.clone()/>
+        <h1 class="text-2xl">
+            <a href=url.clone() class="hover:underline">{name}</a>
+            <span class="ml-2 text-sm text-gray-400">{forge}</span>
+            <span class="ml-2">
+                <FollowButton kind=FollowKind::Project target=url/>
+            </span>
+        </h1>
+        {metrics
+            .map(|m| {
+                view! {
+                    {m.description.map(|d| view! { <p>{d}</p> })}
+                    <div class="flex flex-wrap gap-2 items-center">
+                        {m.is_archived.then(|| view! { <span class="badge badge-warning">"archived"</span> })}
+                        <span>"★ " {m.stars}</span>
+                        <span>{m.forks} " forks"</span>
+                        {m.primary_language.map(|l| view! { <span>{l}</span> })}
+                        <span>{m.license.unwrap_or_else(|| "no license".to_string())}</span>
+                        {m
+                            .pushed_at
+                            .map(|p| view! { <span>"last push " {p.format("%Y-%m-%d").to_string()}</span> })}
+                        {m
+                            .homepage_url
+                            .filter(|h| !h.is_empty())
+                            .map(|h| view! { <a href=h.clone() class="hover:underline">{h}</a> })}
+                    </div>
+                    <div class="flex flex-wrap gap-1">
+                        {m
+                            .topics
+                            .into_iter()
+                            .map(|t| view! { <span class="badge badge-outline">{t}</span> })
+                            .collect_view()}
+                    </div>
+                }
+            })}
+        {(stars.len() > 1)
+            .then(|| {
+                view! {
+                    <div class="flex gap-2 items-center text-sm text-gray-400">
+                        <svg viewBox="0 0 200 40" class="w-48 h-10" preserveAspectRatio="none">
+                            <polyline points=sparkline(&stars, 200.0, 40.0) fill="none" stroke="currentColor" stroke-width="2"/>
+                        </svg>
+                        "stars " {history_range}
+                    </div>
+                }
+            })}
+        {equivalents
+            .map(|href| {
+                view! {
+                    <A href class="hover:underline">
+                        "Equivalents in other languages"
+                    </A>
+                }
+            })}
+        <h2 class="text-xl">"Listed in"</h2>
+        <ul class="flex flex-col gap-2">
+            {listings
+                .into_iter()
+                .map(|l| {
+                    view! {
+                        <li>
+                            <A href=format!("/lists/{}", l.list) class="font-bold hover:underline">
+                                {l.list.clone()}
+                            </A>
+                            <span class="text-sm text-gray-400">" › " {l.breadcrumbs.join(" › ")}</span>
+                            <p>
+                                {l.title}
+                                {l.description.map(|d| view! { <span class="text-gray-300">" - " {d}</span> })}
+                            </p>
+                        </li>
+                    }
+                })
+                .collect_view()}
+        </ul>
+    }
+}
+
+#[component]
+fn RelatedProjects(related: Vec<RelatedProject>) -> impl IntoView {
+    if related.is_empty() {
+        return view! { <p class="text-gray-400">"Nothing found."</p> }.into_view();
+    }
+
+    view! {
+        <ul>
+            {related
+                .into_iter()
+                .map(|r| {
+                    let href = project_path(&r.url).unwrap_or_else(|| r.url.clone());
+                    view! {
+                        <li class="my-1">
+                            <a href=href class="hover:underline">{r.title}</a>
+                            {r.stars.map(|s| view! { <span class="ml-2 text-xs">"★ " {s}</span> })}
+                            {r.description.map(|d| view! { <span class="text-gray-300">" - " {d}</span> })}
+                            {(r.shared > 1)
+                                .then(|| {
+                                    view! {
+                                        <span class="ml-2 text-xs text-gray-400">
+                                            {r.shared} " shared categories"
+                                        </span>
+                                    }
+                                })}
+                        </li>
+                    }
+                })
+                .collect_view()}
+        </ul>
+    }
+    .into_view()
+}
+
+/// Points of an SVG polyline scaling the values to the box, the lowest value
+/// at the bottom.
+fn sparkline(values: &[i32], width: f64, height: f64) -> String {
+    let min = values.iter().copied().min().unwrap_or_default() as f64;
+    let max = values.iter().copied().max().unwrap_or_default() as f64;
+    let step = width / (values.len().max(2) - 1) as f64;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let y = if max > min {
+                height - (*v as f64 - min) / (max - min) * height
+            } else {
+                height / 2.0
+            };
+            format!("{:.1},{:.1}", i as f64 * step, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_path() {
+        assert_eq!(
+            project_path("https://github.com/rust-lang/rust").as_deref(),
+            Some("/projects/github/rust-lang/rust")
+        );
+        assert_eq!(
+            project_path("http://www.GitLab.com/gitlab-org/gitlab/").as_deref(),
+            Some("/projects/gitlab/gitlab-org/gitlab")
+        );
+        assert_eq!(
+            project_path("https://codeberg.org/forgejo/forgejo.git").as_deref(),
+            Some("/projects/codeberg/forgejo/forgejo")
+        );
+        assert_eq!(project_path("https://example.com/owner/repo"), None);
+        assert_eq!(project_path("https://github.com/rust-lang"), None);
+        assert_eq!(
+            project_path("https://github.com/rust-lang/rust/issues"),
+            None
+        );
+        assert_eq!(project_path("ftp://github.com/rust-lang/rust"), None);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(
+            sparkline(&[1, 2, 3], 200.0, 40.0),
+            "0.0,40.0 100.0,20.0 200.0,0.0"
+        );
+        assert_eq!(sparkline(&[5, 5], 200.0, 40.0), "0.0,20.0 200.0,20.0");
+        assert_eq!(sparkline(&[7], 200.0, 40.0), "0.0,20.0");
+        assert_eq!(sparkline(&[], 200.0, 40.0), "");
+    }
+}
@@ -0,0 +1,600 @@
+use crate::projects::{project_details, ProjectListing, ProjectMetrics};
+use crate::tree::category_breadcrumbs;
+use axum::{
+    extract::{rejection::QueryRejection, FromRequestParts, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use uuid::Uuid;
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+/// The versioned public API, nested under `/api/v1`.
+pub fn router() -> Router<PgPool> {
+    Router::new()
+        .route("/openapi.json", get(openapi))
+        .route("/lists", get(lists))
+        .route("/lists/:owner/:repo", get(list))
+        .route("/lists/:owner/:repo/links", get(list_links))
+        .route("/links", get(links))
+        .route("/projects/:forge/:owner/:repo", get(project))
+        .route("/search", get(search_links))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "EpicList", version = "1"),
+    servers((url = "/api/v1")),
+    paths(lists, list, list_links, links, project, search_links),
+    components(schemas(
+        List,
+        Link,
+        ListPage,
+        LinkPage,
+        Project,
+        ProjectListing,
+        ProjectMetrics,
+        StarCount,
+        SearchResult,
+        SearchResults,
+        ApiError,
+    ))
+)]
+pub struct ApiDoc;
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiError {
+    pub error: String,
+}
+
+/// Failed requests answer with a status and an `ApiError` body.
+#[derive(Debug)]
+pub enum Error {
+    NotFound(&'static str),
+    BadRequest(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(e: sqlx::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            Error::NotFound(what) => (StatusCode::NOT_FOUND, format!("{what} not found")),
+            Error::BadRequest(error) => (StatusCode::BAD_REQUEST, error),
+            Error::Database(e) => {
+                log::error!("api: {e}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "database error".to_string(),
+                )
+            }
+        };
+        (status, Json(ApiError { error })).into_response()
+    }
+}
+
+impl From<QueryRejection> for Error {
+    fn from(e: QueryRejection) -> Self {
+        Error::BadRequest(e.body_text())
+    }
+}
+
+/// `Query`, but missing or malformed parameters also answer with an
+/// `ApiError`.
+#[derive(FromRequestParts)]
+#[from_request(via(Query), rejection(Error))]
+struct ApiQuery<T>(T);
+
+/// Items in a stable order, pass `next_cursor` back as `cursor` for the
+/// next page. It's `null` on the last page.
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(ListPage = Page<List>, LinkPage = Page<Link>)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// The parsed cursor and one more than the page size, to tell whether
+/// there's a next page.
+fn window(cursor: Option<&str>, limit: Option<i64>) -> Result<(Option<Uuid>, i64), Error> {
+    let cursor = match cursor.filter(|c| !c.is_empty()) {
+        Some(cursor) => Some(
+            cursor
+                .parse()
+                .map_err(|_| Error::BadRequest(format!("invalid cursor {cursor}")))?,
+        ),
+        None => None,
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    Ok((cursor, limit + 1))
+}
+
+/// Pages are ordered by id, the cursor is the id of the last item.
+fn page<R, T>(
+    mut rows: Vec<R>,
+    fetched: i64,
+    id: impl Fn(&R) -> Uuid,
+    item: impl Fn(R) -> T,
+) -> Page<T> {
+    let next_cursor = if rows.len() as i64 == fetched {
+        rows.pop();
+        rows.last().map(|r| id(r).to_string())
+    } else {
+        None
+    };
+    Page {
+        items: rows.into_iter().map(item).collect(),
+        next_cursor,
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct List {
+    pub owner: String,
+    pub repo: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub stars: Option<i32>,
+    pub link_count: i32,
+    pub topics: Vec<String>,
+    pub latest_commit_at: DateTime<Utc>,
+    pub crawled_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Link {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub breadcrumbs: Vec<String>,
+    /// One of the `AwesomeLinkType` variants, e.g. `Repo`
+    pub link_type: Option<String>,
+    /// The list linking to it, as `owner/repo`
+    pub list: String,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct ListsParams {
+    /// From `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// At most 200, 50 by default
+    pub limit: Option<i64>,
+    /// Only lists with this topic
+    pub topic: Option<String>,
+}
+
+/// All ingested awesome lists.
+#[utoipa::path(
+    get,
+    path = "/lists",
+    params(ListsParams),
+    responses(
+        (status = 200, body = ListPage),
+        (status = 400, body = ApiError),
+    )
+)]
+async fn lists(
+    State(pool): State<PgPool>,
+    ApiQuery(params): ApiQuery<ListsParams>,
+) -> Result<Json<Page<List>>, Error> {
+    let (cursor, fetch) = window(params.cursor.as_deref(), params.limit)?;
+    let rows = sqlx::query!(
+        r"--sql
+        SELECT
+            id,
+            owner,
+            repo,
+            url,
+            description,
+            stars,
+            link_count,
+            topics,
+            latest_commit_at,
+            crawled_at
+        FROM
+            awesome_lists
+        WHERE
+            owner IS NOT NULL
+            AND ($1::uuid IS NULL OR id > $1)
+            AND ($2::text IS NULL OR $2 = ANY (topics))
+        ORDER BY
+            id
+        LIMIT $3
+        ",
+        cursor,
+        params.topic,
+        fetch,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(page(
+        rows,
+        fetch,
+        |r| r.id,
+        |r| List {
+            owner: r.owner.unwrap_or_default(),
+            repo: r.repo.unwrap_or_default(),
+            url: r.url,
+            description: r.description,
+            stars: r.stars,
+            link_count: r.link_count,
+            topics: r.topics,
+            latest_commit_at: r.latest_commit_at,
+            crawled_at: r.crawled_at,
+        },
+    )))
+}
+
+/// One awesome list, without its links.
+#[utoipa::path(
+    get,
+    path = "/lists/{owner}/{repo}",
+    params(("owner" = String, Path), ("repo" = String, Path)),
+    responses(
+        (status = 200, body = List),
+        (status = 404, body = ApiError),
+    )
+)]
+async fn list(
+    State(pool): State<PgPool>,
+    Path((owner, repo)): Path<(String, String)>,
+) -> Result<Json<List>, Error> {
+    let list = sqlx::query!(
+        r"--sql
+        SELECT
+            owner,
+            repo,
+            url,
+            description,
+            stars,
+            link_count,
+            topics,
+            latest_commit_at,
+            crawled_at
+        FROM
+            awesome_lists
+        WHERE
+            lower(owner) = lower($1)
+            AND lower(repo) = lower($2)
+        ",
+        owner,
+        repo,
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(Error::NotFound("list"))?;
+
+    Ok(Json(List {
+        owner: list.owner.unwrap_or(owner),
+        repo: list.repo.unwrap_or(repo),
+        url: list.url,
+        description: list.description,
+        stars: list.stars,
+        link_count: list.link_count,
+        topics: list.topics,
+        latest_commit_at: list.latest_commit_at,
+        crawled_at: list.crawled_at,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct ListLinksParams {
+    /// From `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// At most 200, 50 by default
+    pub limit: Option<i64>,
+    /// Only links in this category and below it, the breadcrumbs joined by
+    /// ` › `
+    pub category: Option<String>,
+}
+
+/// The links currently in a list.
+#[utoipa::path(
+    get,
+    path = "/lists/{owner}/{repo}/links",
+    params(("owner" = String, Path), ("repo" = String, Path), ListLinksParams),
+    responses(
+        (status = 200, body = LinkPage),
+        (status = 400, body = ApiError),
+        (status = 404, body = ApiError),
+    )
+)]
+async fn list_links(
+    State(pool): State<PgPool>,
+    Path((owner, repo)): Path<(String, String)>,
+    ApiQuery(params): ApiQuery<ListLinksParams>,
+) -> Result<Json<Page<Link>>, Error> {
+    let (cursor, fetch) = window(params.cursor.as_deref(), params.limit)?;
+    let breadcrumbs = params
+        .category
+        .as_deref()
+        .map(category_breadcrumbs)
+        .unwrap_or_default();
+
+    let list = sqlx::query!(
+        r"--sql
+        SELECT
+            id,
+            owner,
+            repo
+        FROM
+            awesome_lists
+        WHERE
+            lower(owner) = lower($1)
+            AND lower(repo) = lower($2)
+        ",
+        owner,
+        repo,
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(Error::NotFound("list"))?;
+    let name = format!(
+        "{}/{}",
+        list.owner.unwrap_or(owner),
+        list.repo.unwrap_or(repo)
+    );
+
+    let rows = sqlx::query!(
+        r#"--sql
+        SELECT
+            id AS "id!",
+            url AS "url!",
+            title,
+            description,
+            breadcrumbs AS "breadcrumbs!",
+            link_type
+        FROM
+            current_awesome_links
+        WHERE
+            awesome_list_id = $1
+            AND breadcrumbs[1:cardinality($2::text[])] = $2
+            AND ($3::uuid IS NULL OR id > $3)
+        ORDER BY
+            id
+        LIMIT $4
+        "#,
+        list.id,
+        &breadcrumbs,
+        cursor,
+        fetch,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(page(
+        rows,
+        fetch,
+        |r| r.id,
+        |r| Link {
+            url: r.url,
+            title: r.title,
+            description: r.description,
+            breadcrumbs: r.breadcrumbs,
+            link_type: r.link_type,
+            list: name.clone(),
+        },
+    )))
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct LinksParams {
+    /// From `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// At most 200, 50 by default
+    pub limit: Option<i64>,
+    /// Only links to this url, ignoring case and a trailing slash
+    pub url: Option<String>,
+    /// One of the `AwesomeLinkType` variants, e.g. `Repo`
+    #[serde(rename = "type")]
+    #[param(rename = "type")]
+    pub link_type: Option<String>,
+}
+
+/// The links currently in any list.
+#[utoipa::path(
+    get,
+    path = "/links",
+    params(LinksParams),
+    responses(
+        (status = 200, body = LinkPage),
+        (status = 400, body = ApiError),
+    )
+)]
+async fn links(
+    State(pool): State<PgPool>,
+    ApiQuery(params): ApiQuery<LinksParams>,
+) -> Result<Json<Page<Link>>, Error> {
+    let (cursor, fetch) = window(params.cursor.as_deref(), params.limit)?;
+    let rows = sqlx::query!(
+        r#"--sql
+        SELECT
+            l.id AS "id!",
+            l.url AS "url!",
+            l.title,
+            l.description,
+            l.breadcrumbs AS "breadcrumbs!",
+            l.link_type,
+            al.owner,
+            al.repo
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+        WHERE ($1::text IS NULL
+            OR lower(rtrim(l.url, '/')) = lower(rtrim($1, '/')))
+            AND ($2::text IS NULL OR lower(l.link_type) = lower($2))
+            AND ($3::uuid IS NULL OR l.id > $3)
+        ORDER BY
+            l.id
+        LIMIT $4
+        "#,
+        params.url,
+        params.link_type,
+        cursor,
+        fetch,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(page(
+        rows,
+        fetch,
+        |r| r.id,
+        |r| Link {
+            url: r.url,
+            title: r.title,
+            description: r.description,
+            breadcrumbs: r.breadcrumbs,
+            link_type: r.link_type,
+            list: format!(
+                "{}/{}",
+                r.owner.unwrap_or_default(),
+                r.repo.unwrap_or_default()
+            ),
+        },
+    )))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StarCount {
+    pub taken_at: DateTime<Utc>,
+    pub stars: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Project {
+    pub url: String,
+    /// The lists linking to the project
+    pub listings: Vec<ProjectListing>,
+    /// Only known for GitHub
+    pub metrics: Option<ProjectMetrics>,
+    /// Oldest first
+    pub star_history: Vec<StarCount>,
+}
+
+/// A linked project with what its forge says about it.
+#[utoipa::path(
+    get,
+    path = "/projects/{forge}/{owner}/{repo}",
+    params(
+        ("forge" = String, Path, description = "`github`, `gitlab`, `codeberg` or `bitbucket`"),
+        ("owner" = String, Path),
+        ("repo" = String, Path),
+    ),
+    responses(
+        (status = 200, body = Project),
+        (status = 404, body = ApiError),
+    )
+)]
+async fn project(
+    State(pool): State<PgPool>,
+    Path((forge, owner, repo)): Path<(String, String, String)>,
+) -> Result<Json<Project>, Error> {
+    let details = project_details(&pool, &forge, &owner, &repo)
+        .await?
+        .ok_or(Error::NotFound("project"))?;
+
+    Ok(Json(Project {
+        url: details.url,
+        listings: details.listings,
+        metrics: details.metrics,
+        star_history: details
+            .star_history
+            .into_iter()
+            .map(|(taken_at, stars)| StarCount { taken_at, stars })
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct SearchParams {
+    /// Free text, supports `"quoted phrases"`, `or` and `-negation`
+    pub q: String,
+    #[serde(rename = "type")]
+    #[param(rename = "type")]
+    pub link_type: Option<String>,
+    /// Only links from this list, as `owner/repo`
+    pub list: Option<String>,
+    pub language: Option<String>,
+    /// At most 200, 50 by default
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResult {
+    pub url: String,
+    pub title: String,
+    pub breadcrumbs: Vec<String>,
+    pub description: Option<String>,
+    pub link_type: Option<String>,
+    pub list: String,
+    pub language: Option<String>,
+    pub score: f64,
+}
+
+/// Ranked by relevance, so not paginated.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResults {
+    pub items: Vec<SearchResult>,
+}
+
+/// Full-text search over link titles, descriptions and breadcrumbs.
+#[utoipa::path(
+    get,
+    path = "/search",
+    params(SearchParams),
+    responses(
+        (status = 200, body = SearchResults),
+        (status = 400, body = ApiError),
+    )
+)]
+async fn search_links(
+    State(pool): State<PgPool>,
+    ApiQuery(params): ApiQuery<SearchParams>,
+) -> Result<Json<SearchResults>, Error> {
+    if params.q.trim().is_empty() {
+        return Err(Error::BadRequest("q is required".to_string()));
+    }
+    let query = search::SearchQuery {
+        text: params.q,
+        link_type: params.link_type,
+        list: params.list,
+        language: params.language,
+        pushed_since: None,
+        limit: params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+    };
+    let hits = search::search(&pool, &query, None).await?;
+
+    Ok(Json(SearchResults {
+        items: hits
+            .into_iter()
+            .map(|h| SearchResult {
+                url: h.url,
+                title: h.title,
+                breadcrumbs: h.breadcrumbs,
+                description: h.description,
+                link_type: h.link_type,
+                list: h.list,
+                language: h.language,
+                score: h.score,
+            })
+            .collect(),
+    }))
+}
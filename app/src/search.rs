@@ -0,0 +1,210 @@
+use crate::components::{SearchHits, ServerErrors};
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::*;
+use search::{Facet, Facets, SearchHit};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long typing has to pause before the results update.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    pub facets: Facets,
+}
+
+/// Facets are counted over all matches without any filters, so picking one
+/// doesn't hide the others.
+#[server(SearchLinks, "/api")]
+pub async fn search_links(
+    text: String,
+    link_type: Option<String>,
+    list: Option<String>,
+    language: Option<String>,
+) -> Result<SearchResults, ServerFnError> {
+    if text.trim().is_empty() {
+        return Ok(SearchResults {
+            hits: vec![],
+            facets: Facets::default(),
+        });
+    }
+    let pool = crate::state::pool()?;
+
+    let facets = search::facets(&pool, &text, 10)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+    let query = search::SearchQuery {
+        text,
+        link_type,
+        list,
+        language,
+        limit: 50,
+        ..Default::default()
+    };
+    let hits = search::search(&pool, &query, None)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+
+    Ok(SearchResults { hits, facets })
+}
+
+/// The words to mark in the results, leaving out `or` and negated words.
+fn search_terms(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter(|w| !w.starts_with('-') && !w.eq_ignore_ascii_case("or"))
+        .map(|w| w.trim_matches('"').to_ascii_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Full-text search over all links. Results follow the query string, so
+/// every search can be shared, and update while typing once hydrated.
+#[component]
+pub fn Search() -> impl IntoView {
+    let query = use_query_map();
+    let param = move |key: &str| query.with(|q| q.get(key).filter(|v| !v.is_empty()).cloned());
+    let text = move || param("q").unwrap_or_default();
+
+    let results = create_resource(
+        move || (text(), param("type"), param("list"), param("language")),
+        |(text, link_type, list, language)| search_links(text, link_type, list, language),
+    );
+
+    // sets or removes one parameter, keeping the others
+    let href = move |key: &str, value: Option<String>| {
+        let mut params = query.get();
+        match value {
+            Some(value) => params.insert(key.to_string(), value),
+            None => params.remove(key),
+        };
+        format!("/search{}", params.to_query_string())
+    };
+
+    let navigate = use_navigate();
+    let on_input = debounce(DEBOUNCE, move |ev: ev::Event| {
+        let url = href("q", Some(event_target_value(&ev)));
+        navigate(
+            &url,
+            NavigateOptions {
+                replace: true,
+                ..Default::default()
+            },
+        );
+    });
+
+    let chips = move |key: &'static str, label: &'static str, facets: Vec<Facet>| {
+        let active = param(key);
+        (!facets.is_empty())
+            .then(|| {
+                view! {
+                    <div class="flex flex-wrap gap-1 items-center">
+                        <span class="text-sm text-gray-400">{label}</span>
+                        {facets
+                            .into_iter()
+                            .map(|f| {
+                                let selected = active.as_deref() == Some(f.value.as_str());
+                                let link = href(key, (!selected).then(|| f.value.clone()));
+                                view! {
+                                    <A
+                                        href=link
+                                        class=if selected { "badge badge-primary" } else { "badge badge-outline" }
+                                    >
+                                        {f.value}
+                                        " "
+                                        {if selected { "✕".to_string() } else { f.count.to_string() }}
+                                    </A>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                }
+            })
+    };
+
+    view! {
+        <Title text=move || {
+            match param("q") {
+                Some(q) => format!("{q} - Search"),
+                None => "Search".to_string(),
+            }
+        }/>
+        <h1 class="text-2xl">"Search"</h1>
+        {move || {
+            param("q")
+                .map(|_| {
+                    let params = query.get().to_query_string();
+                    view! {
+                        <p class="text-sm text-gray-400">
+                            "Subscribe to new results: "
+                            <a href=format!("/search/feed.atom{params}") rel="external" class="hover:underline">
+                                "Atom"
+                            </a>
+                            " · "
+                            <a href=format!("/search/feed.rss{params}") rel="external" class="hover:underline">
+                                "RSS"
+                            </a>
+                        </p>
+                    }
+                })
+        }}
+        // still searches on submit without JavaScript
+        <Form method="GET" action="/search" class="flex gap-2">
+            <input
+                type="search"
+                name="q"
+                placeholder="Search all links"
+                autofocus
+                class="w-full max-w-xl text-black input input-bordered"
+                // only the initial value, updating it while typing would
+                // undo keystrokes made during the debounce
+                value=query.with_untracked(|q| q.get("q").cloned())
+                on:input=on_input
+            />
+            {move || {
+                ["type", "list", "language"]
+                    .into_iter()
+                    .filter_map(|key| {
+                        param(key).map(|value| view! { <input type="hidden" name=key value=value/> })
+                    })
+                    .collect_view()
+            }}
+            <button type="submit" class="btn">"Search"</button>
+        </Form>
+        <Transition fallback=move || view! { <p>"Loading..."</p> }>
+            <ErrorBoundary fallback=|errors| view! { <ServerErrors errors/> }>
+                {move || {
+                    results
+                        .get()
+                        .map(|results| {
+                            results
+                                .map(|results| {
+                                    view! {
+                                        {chips("type", "Type", results.facets.link_types)}
+                                        {chips("list", "List", results.facets.lists)}
+                                        {chips("language", "Language", results.facets.languages)}
+                                        <SearchHits hits=results.hits terms=search_terms(&text())/>
+                                    }
+                                })
+                        })
+                }}
+            </ErrorBoundary>
+        </Transition>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(
+            search_terms(r#"Async "web framework" OR http -python"#),
+            ["async", "web", "framework", "http"]
+        );
+        assert_eq!(search_terms(r#"  "" or -  "#), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,20 @@
+use cfg_if::cfg_if;
+
+cfg_if! { if #[cfg(feature = "ssr")] {
+    use axum::extract::FromRef;
+    use leptos::*;
+    use sqlx::PgPool;
+
+    /// Everything the axum handlers need, server functions get the pool
+    /// through leptos' context instead.
+    #[derive(FromRef, Debug, Clone)]
+    pub struct AppState {
+        pub leptos_options: LeptosOptions,
+        pub pool: PgPool,
+    }
+
+    pub fn pool() -> Result<PgPool, ServerFnError> {
+        use_context::<PgPool>()
+            .ok_or_else(|| ServerFnError::ServerError("database pool missing".into()))
+    }
+}}
@@ -0,0 +1,554 @@
+use crate::tree::category_breadcrumbs;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Entries per feed, readers only poll for what's new.
+const ENTRIES: i64 = 50;
+
+/// Lists are crawled a few times a day at most.
+const CACHE_CONTROL: &str = "public, max-age=900";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Atom,
+    Rss,
+}
+
+impl Format {
+    /// The last path segment, `feed.atom` or `feed.rss`.
+    fn parse(file: &str) -> Option<Self> {
+        match file {
+            "feed.atom" => Some(Self::Atom),
+            "feed.rss" => Some(Self::Rss),
+            _ => None,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Atom => "application/atom+xml; charset=utf-8",
+            Self::Rss => "application/rss+xml; charset=utf-8",
+        }
+    }
+}
+
+/// A newly added link. The link's id doesn't change when it's moved or
+/// edited, so readers don't show it twice.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    id: Uuid,
+    url: String,
+    title: String,
+    description: Option<String>,
+    /// As `owner/repo`
+    list: String,
+    breadcrumbs: Vec<String>,
+    added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Channel {
+    title: String,
+    /// Stable across hosts, unlike the feed's own url
+    id: String,
+    /// The app page showing the same links, relative to the site
+    page: String,
+    entries: Vec<Entry>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Percent-encodes everything but unreserved characters, so any text can be
+/// part of a feed's URN.
+fn urn_segment(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn entry_title(entry: &Entry) -> String {
+    if entry.title.is_empty() {
+        entry.url.clone()
+    } else {
+        entry.title.clone()
+    }
+}
+
+fn entry_category(entry: &Entry) -> String {
+    std::iter::once(entry.list.as_str())
+        .chain(entry.breadcrumbs.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" › ")
+}
+
+fn render_atom(channel: &Channel, site: &str, self_url: &str) -> String {
+    let updated = channel
+        .entries
+        .iter()
+        .map(|e| e.added_at)
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><feed xmlns="http://www.w3.org/2005/Atom"><title>{}</title><id>{}</id><link rel="self" href="{}"/><link rel="alternate" href="{}"/><updated>{}</updated><generator>EpicList</generator>"#,
+        escape(&channel.title),
+        escape(&channel.id),
+        escape(self_url),
+        escape(&format!("{site}{}", channel.page)),
+        updated.to_rfc3339(),
+    );
+    for entry in &channel.entries {
+        xml.push_str(&format!(
+            r#"<entry><title>{}</title><id>urn:uuid:{}</id><link href="{}"/><published>{}</published><updated>{}</updated><category term="{}"/><author><name>{}</name></author>"#,
+            escape(&entry_title(entry)),
+            entry.id,
+            escape(&entry.url),
+            entry.added_at.to_rfc3339(),
+            entry.added_at.to_rfc3339(),
+            escape(&entry_category(entry)),
+            escape(&entry.list),
+        ));
+        if let Some(description) = &entry.description {
+            xml.push_str(&format!("<summary>{}</summary>", escape(description)));
+        }
+        xml.push_str("</entry>");
+    }
+    xml.push_str("</feed>");
+    xml
+}
+
+fn render_rss(channel: &Channel, site: &str, self_url: &str) -> String {
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel><title>{}</title><link>{}</link><description>{}</description><atom:link rel="self" type="application/rss+xml" href="{}"/><generator>EpicList</generator>"#,
+        escape(&channel.title),
+        escape(&format!("{site}{}", channel.page)),
+        escape(&format!("Links newly added to {}", channel.title)),
+        escape(self_url),
+    );
+    if let Some(updated) = channel.entries.iter().map(|e| e.added_at).max() {
+        xml.push_str(&format!(
+            "<lastBuildDate>{}</lastBuildDate>",
+            updated.to_rfc2822()
+        ));
+    }
+    for entry in &channel.entries {
+        xml.push_str(&format!(
+            r#"<item><title>{}</title><link>{}</link><guid isPermaLink="false">urn:uuid:{}</guid><pubDate>{}</pubDate><category>{}</category>"#,
+            escape(&entry_title(entry)),
+            escape(&entry.url),
+            entry.id,
+            entry.added_at.to_rfc2822(),
+            escape(&entry_category(entry)),
+        ));
+        if let Some(description) = &entry.description {
+            xml.push_str(&format!(
+                "<description>{}</description>",
+                escape(description)
+            ));
+        }
+        xml.push_str("</item>");
+    }
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+/// Where the app is reached from, feeds need absolute links.
+fn site(headers: &HeaderMap) -> String {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost");
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("http");
+    format!("{scheme}://{host}")
+}
+
+fn respond(channel: Channel, format: Format, headers: &HeaderMap, uri: &Uri) -> Response {
+    let site = site(headers);
+    let self_url = match uri.path_and_query() {
+        Some(path) => format!("{site}{path}"),
+        None => format!("{site}{}", uri.path()),
+    };
+    let body = match format {
+        Format::Atom => render_atom(&channel, &site, &self_url),
+        Format::Rss => render_rss(&channel, &site, &self_url),
+    };
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type()),
+            (header::CACHE_CONTROL, CACHE_CONTROL),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn server_error(e: sqlx::Error) -> Response {
+    log::error!("feed: {e}");
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+/// `?category=Web Frameworks › Async`, the breadcrumbs as the app shows them.
+#[derive(Debug, Default, Deserialize)]
+pub struct ListFeedQuery {
+    pub category: Option<String>,
+}
+
+/// Links newly added to a list, or to a category and everything below it,
+/// at `/lists/:owner/:repo/feed.atom` and `/lists/:owner/:repo/feed.rss`.
+pub async fn list_feed(
+    State(pool): State<PgPool>,
+    Path((owner, repo, file)): Path<(String, String, String)>,
+    Query(query): Query<ListFeedQuery>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Response {
+    let Some(format) = Format::parse(&file) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let breadcrumbs = query
+        .category
+        .as_deref()
+        .map(category_breadcrumbs)
+        .unwrap_or_default();
+
+    let list = match sqlx::query!(
+        r"--sql
+        SELECT
+            id,
+            owner,
+            repo
+        FROM
+            awesome_lists
+        WHERE
+            lower(owner) = lower($1)
+            AND lower(repo) = lower($2)
+        ",
+        owner,
+        repo,
+    )
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(Some(list)) => list,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => return server_error(e),
+    };
+    let name = format!(
+        "{}/{}",
+        list.owner.unwrap_or(owner),
+        list.repo.unwrap_or(repo)
+    );
+
+    let entries = match sqlx::query!(
+        r#"--sql
+        SELECT
+            l.id AS "id!",
+            l.url AS "url!",
+            l.title,
+            l.description,
+            l.breadcrumbs AS "breadcrumbs!",
+            coalesce((
+                SELECT
+                    max(e.occurred_at)
+                FROM awesome_link_events e
+                WHERE
+                    e.awesome_link_id = l.id
+                    AND e.kind = 'added'), al.crawled_at) AS "added_at!"
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+        WHERE
+            l.awesome_list_id = $1
+            AND l.breadcrumbs[1:cardinality($2::text[])] = $2
+        ORDER BY
+            6 DESC,
+            l.id DESC
+        LIMIT $3
+        "#,
+        list.id,
+        &breadcrumbs,
+        ENTRIES,
+    )
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(entries) => entries,
+        Err(e) => return server_error(e),
+    };
+
+    let title = std::iter::once(name.clone())
+        .chain(breadcrumbs.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" › ");
+    let channel = Channel {
+        id: std::iter::once(format!("urn:epiclist:lists:{}", list.id))
+            .chain(breadcrumbs.iter().map(|b| urn_segment(b)))
+            .collect::<Vec<_>>()
+            .join(":"),
+        title,
+        page: format!("/lists/{name}"),
+        entries: entries
+            .into_iter()
+            .map(|e| Entry {
+                id: e.id,
+                url: e.url,
+                title: e.title.unwrap_or_default(),
+                description: e.description,
+                list: name.clone(),
+                breadcrumbs: e.breadcrumbs,
+                added_at: e.added_at,
+            })
+            .collect(),
+    };
+    respond(channel, format, &headers, &uri)
+}
+
+/// The parameters of the search page, so any search can be subscribed to
+/// by swapping `/search` for `/search/feed.atom`.
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchFeedQuery {
+    pub q: Option<String>,
+    #[serde(rename = "type")]
+    pub link_type: Option<String>,
+    pub list: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Links matching a search, newest first, at `/search/feed.atom` and
+/// `/search/feed.rss`.
+pub async fn search_feed(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<SearchFeedQuery>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Response {
+    let Some(format) = Format::parse(&file) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let nonempty = |v: &Option<String>| v.clone().filter(|v| !v.trim().is_empty());
+    let Some(text) = nonempty(&query.q) else {
+        return (StatusCode::BAD_REQUEST, "q is required").into_response();
+    };
+    let (link_type, list, language) = (
+        nonempty(&query.link_type),
+        nonempty(&query.list),
+        nonempty(&query.language),
+    );
+
+    let entries = match sqlx::query!(
+        r#"--sql
+        SELECT
+            l.id AS "id!",
+            l.url AS "url!",
+            l.title,
+            l.description,
+            l.breadcrumbs AS "breadcrumbs!",
+            al.owner,
+            al.repo,
+            coalesce((
+                SELECT
+                    max(e.occurred_at)
+                FROM awesome_link_events e
+                WHERE
+                    e.awesome_link_id = l.id
+                    AND e.kind = 'added'), al.crawled_at) AS "added_at!"
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/')),
+            websearch_to_tsquery('english', $1) q
+        WHERE
+            l.search_vector @@ q
+            AND ($2::text IS NULL OR lower(l.link_type) = lower($2))
+            AND ($3::text IS NULL OR lower(al.owner || '/' || al.repo) = lower($3))
+            AND ($4::text IS NULL
+                OR lower(gp.primary_language) = lower($4)
+                OR lower(al.repo) = 'awesome-' || lower($4))
+        ORDER BY
+            8 DESC,
+            l.id DESC
+        LIMIT $5
+        "#,
+        text,
+        link_type,
+        list,
+        language,
+        ENTRIES,
+    )
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(entries) => entries,
+        Err(e) => return server_error(e),
+    };
+
+    let id = [
+        Some(&text),
+        link_type.as_ref(),
+        list.as_ref(),
+        language.as_ref(),
+    ]
+    .map(|v| {
+        v.map(|v| urn_segment(&v.to_lowercase()))
+            .unwrap_or_default()
+    })
+    .join(":");
+    let channel = Channel {
+        id: format!("urn:epiclist:search:{id}"),
+        title: format!("Search: {text}"),
+        page: format!("/search?{}", uri.query().unwrap_or_default()),
+        entries: entries
+            .into_iter()
+            .map(|e| Entry {
+                id: e.id,
+                url: e.url,
+                title: e.title.unwrap_or_default(),
+                description: e.description,
+                list: format!(
+                    "{}/{}",
+                    e.owner.unwrap_or_default(),
+                    e.repo.unwrap_or_default()
+                ),
+                breadcrumbs: e.breadcrumbs,
+                added_at: e.added_at,
+            })
+            .collect(),
+    };
+    respond(channel, format, &headers, &uri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel() -> Channel {
+        Channel {
+            title: "rust-unofficial/awesome-rust › Libraries".to_string(),
+            id: "urn:epiclist:lists:00000000-0000-7000-8000-000000000001:Libraries".to_string(),
+            page: "/lists/rust-unofficial/awesome-rust".to_string(),
+            entries: vec![
+                Entry {
+                    id: Uuid::from_u128(1),
+                    url: "https://github.com/tokio-rs/tokio".to_string(),
+                    title: "tokio".to_string(),
+                    description: Some("Async <I/O> & more".to_string()),
+                    list: "rust-unofficial/awesome-rust".to_string(),
+                    breadcrumbs: vec!["Libraries".to_string(), "Asynchronous".to_string()],
+                    added_at: "2023-10-02T00:00:00Z".parse().unwrap(),
+                },
+                Entry {
+                    id: Uuid::from_u128(2),
+                    url: "https://example.com/?a=1&b=2".to_string(),
+                    title: String::new(),
+                    description: None,
+                    list: "rust-unofficial/awesome-rust".to_string(),
+                    breadcrumbs: vec!["Libraries".to_string()],
+                    added_at: "2023-09-01T00:00:00Z".parse().unwrap(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+        assert_eq!(escape("&amp;"), "&amp;amp;");
+        assert_eq!(escape("plain › text"), "plain › text");
+    }
+
+    #[test]
+    fn test_urn_segment() {
+        assert_eq!(urn_segment("Web Frameworks"), "Web%20Frameworks");
+        assert_eq!(urn_segment("a › b"), "a%20%E2%80%BA%20b");
+        assert_eq!(urn_segment("C++/CLI:x"), "C%2B%2B%2FCLI%3Ax");
+        assert_eq!(urn_segment("plain-text_1.0~"), "plain-text_1.0~");
+    }
+
+    #[test]
+    fn test_render_atom() {
+        let xml = render_atom(
+            &channel(),
+            "https://epiclist.example",
+            "https://epiclist.example/lists/rust-unofficial/awesome-rust/feed.atom?category=Libraries",
+        );
+        assert!(xml.starts_with(
+            r#"<?xml version="1.0" encoding="utf-8"?><feed xmlns="http://www.w3.org/2005/Atom">"#
+        ));
+        assert!(xml.ends_with("</feed>"));
+        assert!(xml.contains("<title>rust-unofficial/awesome-rust › Libraries</title>"));
+        assert!(xml.contains(
+            "<id>urn:epiclist:lists:00000000-0000-7000-8000-000000000001:Libraries</id>"
+        ));
+        assert!(xml.contains(
+            r#"<link rel="alternate" href="https://epiclist.example/lists/rust-unofficial/awesome-rust"/>"#
+        ));
+        // the newest entry
+        assert!(xml.contains("<updated>2023-10-02T00:00:00+00:00</updated><generator>"));
+        assert!(xml.contains("<id>urn:uuid:00000000-0000-0000-0000-000000000001</id>"));
+        assert!(xml.contains(
+            r#"<category term="rust-unofficial/awesome-rust › Libraries › Asynchronous"/>"#
+        ));
+        assert!(xml.contains("<summary>Async &lt;I/O&gt; &amp; more</summary>"));
+        // untitled links are titled by their url
+        assert!(xml.contains(
+            r#"<title>https://example.com/?a=1&amp;b=2</title><id>urn:uuid:00000000-0000-0000-0000-000000000002</id><link href="https://example.com/?a=1&amp;b=2"/>"#
+        ));
+        assert_eq!(xml.matches("<entry>").count(), 2);
+        assert_eq!(xml.matches("<summary>").count(), 1);
+    }
+
+    #[test]
+    fn test_render_rss() {
+        let xml = render_rss(
+            &channel(),
+            "https://epiclist.example",
+            "https://epiclist.example/lists/rust-unofficial/awesome-rust/feed.rss",
+        );
+        assert!(xml.ends_with("</channel></rss>"));
+        assert!(xml
+            .contains("<link>https://epiclist.example/lists/rust-unofficial/awesome-rust</link>"));
+        assert!(xml.contains(
+            "<description>Links newly added to rust-unofficial/awesome-rust › Libraries</description>"
+        ));
+        assert!(xml.contains("<lastBuildDate>Mon, 2 Oct 2023 00:00:00 +0000</lastBuildDate>"));
+        assert!(xml.contains(
+            r#"<guid isPermaLink="false">urn:uuid:00000000-0000-0000-0000-000000000002</guid><pubDate>Fri, 1 Sep 2023 00:00:00 +0000</pubDate>"#
+        ));
+        assert!(xml.contains("<description>Async &lt;I/O&gt; &amp; more</description>"));
+        assert_eq!(xml.matches("<item>").count(), 2);
+
+        // an empty feed has no build date
+        let empty = Channel {
+            entries: vec![],
+            ..channel()
+        };
+        let xml = render_rss(&empty, "https://epiclist.example", "");
+        assert!(!xml.contains("<lastBuildDate>"));
+        assert!(!xml.contains("<item>"));
+    }
+}
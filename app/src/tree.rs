@@ -0,0 +1,116 @@
+/// Items grouped by their breadcrumbs. Categories and items keep the order
+/// they are passed in, links of a list come in the order of its README.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Category<T> {
+    pub name: String,
+    pub items: Vec<T>,
+    pub children: Vec<Category<T>>,
+}
+
+impl<T> Category<T> {
+    fn new(name: String) -> Self {
+        Category {
+            name,
+            items: vec![],
+            children: vec![],
+        }
+    }
+
+    /// Number of items in this category and all below it.
+    pub fn len(&self) -> usize {
+        self.items.len() + self.children.iter().map(Category::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Builds the tree below an unnamed root, items without breadcrumbs end up
+/// on the root itself.
+pub fn category_tree<T>(items: impl IntoIterator<Item = (Vec<String>, T)>) -> Category<T> {
+    let mut root = Category::new(String::new());
+    for (breadcrumbs, item) in items {
+        let mut node = &mut root;
+        for crumb in breadcrumbs {
+            let index = match node.children.iter().position(|c| c.name == crumb) {
+                Some(index) => index,
+                None => {
+                    node.children.push(Category::new(crumb));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index];
+        }
+        node.items.push(item);
+    }
+    root
+}
+
+/// Breadcrumbs from a category as the app shows them, `Web › Async`.
+pub fn category_breadcrumbs(category: &str) -> Vec<String> {
+    category
+        .split('›')
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crumbs(path: &[&str]) -> Vec<String> {
+        path.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_category_tree() {
+        let tree = category_tree([
+            (crumbs(&["Libraries", "Web"]), "axum"),
+            (crumbs(&["Resources"]), "book"),
+            (crumbs(&["Libraries", "Async"]), "tokio"),
+            (crumbs(&["Libraries", "Web"]), "actix"),
+            (crumbs(&["Libraries"]), "serde"),
+            (crumbs(&[]), "loose"),
+        ]);
+
+        assert_eq!(tree.name, "");
+        assert_eq!(tree.items, ["loose"]);
+        assert_eq!(tree.len(), 6);
+
+        let names = |c: &Category<&str>| {
+            c.children
+                .iter()
+                .map(|c| c.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&tree), ["Libraries", "Resources"]);
+        let libraries = &tree.children[0];
+        assert_eq!(libraries.items, ["serde"]);
+        assert_eq!(names(libraries), ["Web", "Async"]);
+        assert_eq!(libraries.children[0].items, ["axum", "actix"]);
+        assert_eq!(libraries.len(), 4);
+    }
+
+    #[test]
+    fn test_category_breadcrumbs() {
+        assert_eq!(
+            category_breadcrumbs("Web Frameworks › Async"),
+            ["Web Frameworks", "Async"]
+        );
+        assert_eq!(
+            category_breadcrumbs(" Libraries ›› Web › "),
+            ["Libraries", "Web"]
+        );
+        assert!(category_breadcrumbs("").is_empty());
+    }
+
+    #[test]
+    fn test_empty_category_tree() {
+        let tree = category_tree(Vec::<(Vec<String>, ())>::new());
+        assert!(tree.is_empty());
+        assert!(tree.children.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Runs the REST API against a database seeded from `fixtures/seed.sql`.
+//! `sqlx::test` creates a fresh database per test on the server at
+//! `DATABASE_URL`: `cargo test -p app --features ssr --test api`.
+#![cfg(feature = "ssr")]
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use serde_json::Value;
+use sqlx::{migrate::Migrator, PgPool};
+use tower::ServiceExt;
+
+static MIGRATOR: Migrator = sqlx::migrate!("../scraper/migrations/postgres");
+
+async fn get(pool: PgPool, uri: &str) -> (StatusCode, Value) {
+    let response = app::rest::router()
+        .with_state(pool)
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn urls(page: &Value) -> Vec<&str> {
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["url"].as_str().unwrap())
+        .collect()
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_lists_cursor_pagination(pool: PgPool) {
+    let (status, first) = get(pool.clone(), "/lists?limit=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        urls(&first),
+        vec!["https://github.com/rust-unofficial/awesome-rust"]
+    );
+    let cursor = first["next_cursor"].as_str().unwrap();
+
+    let (_, second) = get(pool.clone(), &format!("/lists?limit=1&cursor={cursor}")).await;
+    assert_eq!(
+        urls(&second),
+        vec!["https://github.com/vinta/awesome-python"]
+    );
+    assert!(second["next_cursor"].is_null());
+
+    let (status, _) = get(pool, "/lists?cursor=nope").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_list(pool: PgPool) {
+    let (status, list) = get(pool.clone(), "/lists/Rust-Unofficial/awesome-rust").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["owner"], "rust-unofficial");
+    assert_eq!(list["stars"], 40000);
+
+    let (status, error) = get(pool, "/lists/nobody/awesome-nothing").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error["error"], "list not found");
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_list_links(pool: PgPool) {
+    // the removed async-std link is left out
+    let (status, links) = get(
+        pool.clone(),
+        "/lists/rust-unofficial/awesome-rust/links?category=Libraries",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        urls(&links),
+        vec![
+            "https://github.com/tokio-rs/tokio",
+            "https://github.com/serde-rs/serde",
+        ]
+    );
+    assert_eq!(links["items"][0]["list"], "rust-unofficial/awesome-rust");
+
+    let (_, links) = get(pool, "/lists/rust-unofficial/awesome-rust/links?limit=2").await;
+    assert_eq!(links["items"].as_array().unwrap().len(), 2);
+    assert_eq!(links["next_cursor"], "00000000-0000-7000-8000-000000000012");
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_links(pool: PgPool) {
+    let (_, links) = get(
+        pool.clone(),
+        "/links?url=https://github.com/tokio-rs/tokio/",
+    )
+    .await;
+    assert_eq!(urls(&links), vec!["https://github.com/tokio-rs/tokio"]);
+
+    let (_, books) = get(pool, "/links?type=book").await;
+    assert_eq!(urls(&books), vec!["https://doc.rust-lang.org/book/"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_project(pool: PgPool) {
+    let (status, project) = get(pool.clone(), "/projects/github/tokio-rs/tokio").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(project["metrics"]["stars"], 23000);
+    assert_eq!(
+        project["listings"][0]["list"],
+        "rust-unofficial/awesome-rust"
+    );
+    assert_eq!(project["star_history"].as_array().unwrap().len(), 2);
+
+    let (status, _) = get(pool, "/projects/github/nobody/nothing").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_search(pool: PgPool) {
+    let (status, results) = get(pool.clone(), "/search?q=asynchronous+runtime").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(urls(&results), vec!["https://github.com/tokio-rs/tokio"]);
+
+    let (status, _) = get(pool.clone(), "/search?q=").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // malformed parameters are answered in JSON too
+    let (status, error) = get(pool.clone(), "/search").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .contains("missing field `q`"));
+
+    let (status, error) = get(pool, "/links?limit=many").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error["error"].is_string());
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_openapi(pool: PgPool) {
+    let (status, doc) = get(pool, "/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    for path in [
+        "/lists",
+        "/lists/{owner}/{repo}",
+        "/lists/{owner}/{repo}/links",
+        "/links",
+        "/projects/{forge}/{owner}/{repo}",
+        "/search",
+    ] {
+        assert!(doc["paths"][path].is_object(), "{path} missing");
+    }
+}
@@ -0,0 +1,102 @@
+//! Runs the follows and the feed against the seed of the REST API tests and
+//! a few changes to its links: `just test_api`.
+#![cfg(feature = "ssr")]
+
+use app::feed::{feed, follow_as, follows, unfollow_as, FeedItem, Follow, FollowKind};
+use sqlx::{migrate::Migrator, PgPool};
+use uuid::Uuid;
+
+static MIGRATOR: Migrator = sqlx::migrate!("../scraper/migrations/postgres");
+
+fn follow(kind: FollowKind, target: &str, breadcrumbs: &[&str]) -> Follow {
+    Follow {
+        kind,
+        target: target.to_string(),
+        breadcrumbs: breadcrumbs.iter().map(|b| b.to_string()).collect(),
+    }
+}
+
+fn changes(feed: &[FeedItem]) -> Vec<(&str, &str)> {
+    feed.iter()
+        .map(|item| (item.kind.as_str(), item.title.as_str()))
+        .collect()
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_follows(pool: PgPool) {
+    let (me, someone) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    let list = follow(FollowKind::List, "vinta/awesome-python", &[]);
+    let category = follow(
+        FollowKind::Category,
+        "rust-unofficial/awesome-rust",
+        &["Libraries"],
+    );
+
+    follow_as(&pool, me, &list).await.unwrap();
+    follow_as(&pool, me, &category).await.unwrap();
+    // following twice is a no-op
+    follow_as(&pool, me, &list).await.unwrap();
+    assert_eq!(
+        follows(&pool, me).await.unwrap(),
+        [list.clone(), category.clone()]
+    );
+    assert!(follows(&pool, someone).await.unwrap().is_empty());
+
+    // only the exact category is unfollowed
+    let parent = follow(FollowKind::Category, "rust-unofficial/awesome-rust", &[]);
+    unfollow_as(&pool, me, &parent).await.unwrap();
+    unfollow_as(&pool, someone, &list).await.unwrap();
+    assert_eq!(follows(&pool, me).await.unwrap().len(), 2);
+
+    unfollow_as(&pool, me, &list).await.unwrap();
+    assert_eq!(follows(&pool, me).await.unwrap(), [category]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed", "events"))]
+async fn test_feed(pool: PgPool) {
+    let me = Uuid::from_u128(1);
+    assert!(feed(&pool, me).await.unwrap().is_empty());
+
+    // everything below the category, and the project by any spelling of its url
+    let category = follow(
+        FollowKind::Category,
+        "rust-unofficial/awesome-rust",
+        &["Libraries"],
+    );
+    let project = follow(FollowKind::Project, "https://github.com/PSF/requests/", &[]);
+    follow_as(&pool, me, &category).await.unwrap();
+    follow_as(&pool, me, &project).await.unwrap();
+    let items = feed(&pool, me).await.unwrap();
+    // newest first and without the removal of async-std
+    assert_eq!(
+        changes(&items),
+        [
+            ("added", "tokio"),
+            ("added", "requests"),
+            ("description_changed", "serde"),
+            ("added", "async-std"),
+        ]
+    );
+    assert_eq!(items[1].list, "vinta/awesome-python");
+    assert_eq!(items[0].breadcrumbs, ["Libraries", "Asynchronous"]);
+
+    // a link followed twice shows up once
+    let someone = Uuid::from_u128(2);
+    let list = follow(FollowKind::List, "Rust-Unofficial/awesome-rust", &[]);
+    follow_as(&pool, someone, &list).await.unwrap();
+    follow_as(&pool, someone, &category).await.unwrap();
+    let items = feed(&pool, someone).await.unwrap();
+    assert_eq!(
+        changes(&items),
+        [
+            ("added", "tokio"),
+            ("description_changed", "serde"),
+            ("moved", "The Rust Programming Language"),
+            ("added", "async-std"),
+        ]
+    );
+    assert_eq!(
+        items[2].old_breadcrumbs.as_deref(),
+        Some(&["Books".to_string()][..])
+    );
+}
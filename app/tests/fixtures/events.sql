@@ -0,0 +1,7 @@
+INSERT INTO awesome_link_events(awesome_link_id, kind, occurred_at, old_breadcrumbs, new_breadcrumbs, old_description, new_description)
+  VALUES ('00000000-0000-7000-8000-000000000014', 'added', '2023-08-01T00:00:00Z', NULL, '{Libraries,Asynchronous}', NULL, NULL),
+  ('00000000-0000-7000-8000-000000000013', 'moved', '2023-09-15T00:00:00Z', '{Books}', '{Resources}', NULL, NULL),
+  ('00000000-0000-7000-8000-000000000012', 'description_changed', '2023-09-20T00:00:00Z', NULL, NULL, 'Serialization', 'Serialization framework'),
+  ('00000000-0000-7000-8000-000000000021', 'added', '2023-10-01T00:00:00Z', NULL, '{HTTP}', NULL, NULL),
+  ('00000000-0000-7000-8000-000000000011', 'added', '2023-10-02T00:00:00Z', NULL, '{Libraries,Asynchronous}', NULL, NULL),
+  ('00000000-0000-7000-8000-000000000014', 'removed', '2023-10-02T00:00:00Z', '{Libraries,Asynchronous}', NULL, NULL, NULL);
@@ -0,0 +1,17 @@
+INSERT INTO awesome_lists(id, url, owner, repo, description, stars, topics, link_count, latest_commit_at, crawled_at)
+  VALUES ('00000000-0000-7000-8000-000000000001', 'https://github.com/rust-unofficial/awesome-rust', 'rust-unofficial', 'awesome-rust', 'A curated list of Rust code and resources.', 40000, '{rust}', 3, '2023-10-01T00:00:00Z', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000002', 'https://github.com/vinta/awesome-python', 'vinta', 'awesome-python', 'An opinionated list of awesome Python frameworks.', 180000, '{python}', 1, '2023-09-01T00:00:00Z', '2023-10-02T00:00:00Z');
+
+INSERT INTO awesome_links(id, awesome_list_id, url, title, description, breadcrumbs, link_type, removed_at)
+  VALUES ('00000000-0000-7000-8000-000000000011', '00000000-0000-7000-8000-000000000001', 'https://github.com/tokio-rs/tokio', 'tokio', 'A runtime for writing reliable asynchronous applications', '{Libraries,Asynchronous}', 'Repo', NULL),
+  ('00000000-0000-7000-8000-000000000012', '00000000-0000-7000-8000-000000000001', 'https://github.com/serde-rs/serde', 'serde', 'Serialization framework', '{Libraries,Encoding}', 'Repo', NULL),
+  ('00000000-0000-7000-8000-000000000013', '00000000-0000-7000-8000-000000000001', 'https://doc.rust-lang.org/book/', 'The Rust Programming Language', NULL, '{Resources}', 'Book', NULL),
+  ('00000000-0000-7000-8000-000000000014', '00000000-0000-7000-8000-000000000001', 'https://github.com/async-rs/async-std', 'async-std', 'Async version of the Rust standard library', '{Libraries,Asynchronous}', 'Repo', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000021', '00000000-0000-7000-8000-000000000002', 'https://github.com/psf/requests', 'requests', 'HTTP for Humans', '{HTTP}', 'Repo', NULL);
+
+INSERT INTO github_projects(name_with_owner, url, description, stars, forks, primary_language, license, topics, is_archived, is_fork, pushed_at, enriched_at)
+  VALUES ('tokio-rs/tokio', 'https://github.com/tokio-rs/tokio', 'A runtime for writing reliable asynchronous applications with Rust.', 23000, 2100, 'Rust', 'MIT', '{async,rust}', FALSE, FALSE, '2023-10-01T00:00:00Z', '2023-10-02T00:00:00Z');
+
+INSERT INTO repo_snapshots(name_with_owner, taken_at, stars, forks, open_issues)
+  VALUES ('tokio-rs/tokio', '2023-09-01T00:00:00Z', 22500, 2050, 300),
+  ('tokio-rs/tokio', '2023-10-01T00:00:00Z', 23000, 2100, 310);
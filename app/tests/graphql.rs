@@ -0,0 +1,218 @@
+//! Runs the GraphQL API against the seed of the REST API tests:
+//! `just test_api`.
+#![cfg(feature = "ssr")]
+
+use app::graphql::{GraphqlState, LoaderStats, PersistedQueries};
+use axum::body::Body;
+use http::{header, Request};
+use serde_json::{json, Value};
+use sqlx::{migrate::Migrator, PgPool};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+static MIGRATOR: Migrator = sqlx::migrate!("../scraper/migrations/postgres");
+
+const LIST_QUERY: &str = include_str!("../graphql/list.graphql");
+
+async fn post(pool: PgPool, persisted_queries: Option<PersistedQueries>, body: Value) -> Value {
+    let state = GraphqlState {
+        schema: app::graphql::schema(pool),
+        persisted_queries: persisted_queries.map(Arc::new),
+    };
+    post_to(state, body).await
+}
+
+async fn post_to(state: GraphqlState, body: Value) -> Value {
+    let response = app::graphql::router()
+        .with_state(state)
+        .oneshot(
+            Request::post("/")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_nested_list(pool: PgPool) {
+    let response = post(
+        pool,
+        None,
+        json!({
+            "query": LIST_QUERY,
+            "variables": {
+                "owner": "rust-unofficial",
+                "repo": "awesome-rust",
+                "category": ["Libraries"],
+            },
+        }),
+    )
+    .await;
+    assert!(response["errors"].is_null(), "{response}");
+
+    let links = response["data"]["list"]["links"].as_array().unwrap();
+    let urls = links
+        .iter()
+        .map(|l| l["url"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        vec![
+            "https://github.com/tokio-rs/tokio",
+            "https://github.com/serde-rs/serde",
+        ]
+    );
+    assert_eq!(links[0]["project"]["stars"], 23000);
+    assert_eq!(
+        links[0]["project"]["lists"],
+        json!([{ "owner": "rust-unofficial", "repo": "awesome-rust" }])
+    );
+    assert!(links[1]["project"].is_null());
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_lists_pagination(pool: PgPool) {
+    let query = "query($after: UUID) { lists(first: 1, after: $after) { id repo } }";
+    let first = post(pool.clone(), None, json!({ "query": query })).await;
+    assert_eq!(first["data"]["lists"][0]["repo"], "awesome-rust");
+
+    let after = &first["data"]["lists"][0]["id"];
+    let second = post(
+        pool,
+        None,
+        json!({ "query": query, "variables": { "after": after } }),
+    )
+    .await;
+    assert_eq!(second["data"]["lists"][0]["repo"], "awesome-python");
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_links_pagination(pool: PgPool) {
+    let query = r#"query($after: Int) {
+        list(owner: "rust-unofficial", repo: "awesome-rust") {
+            links(first: 2, after: $after) { position url }
+        }
+    }"#;
+    let first = post(pool.clone(), None, json!({ "query": query })).await;
+    let links = &first["data"]["list"]["links"];
+    assert_eq!(
+        links,
+        &json!([
+            { "position": 1, "url": "https://github.com/tokio-rs/tokio" },
+            { "position": 2, "url": "https://github.com/serde-rs/serde" },
+        ])
+    );
+
+    let second = post(
+        pool,
+        None,
+        json!({ "query": query, "variables": { "after": 2 } }),
+    )
+    .await;
+    assert_eq!(
+        second["data"]["list"]["links"],
+        json!([{ "position": 3, "url": "https://doc.rust-lang.org/book/" }])
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_links_pagination_in_category(pool: PgPool) {
+    let query = r#"{
+        list(owner: "rust-unofficial", repo: "awesome-rust") {
+            links(category: ["Libraries"], first: 1, after: 1) { position url }
+            all: links(first: 1, after: 2) { position url }
+        }
+    }"#;
+    let response = post(pool, None, json!({ "query": query })).await;
+    assert!(response["errors"].is_null(), "{response}");
+    assert_eq!(
+        response["data"]["list"]["links"],
+        json!([{ "position": 2, "url": "https://github.com/serde-rs/serde" }])
+    );
+    assert_eq!(
+        response["data"]["list"]["all"],
+        json!([{ "position": 3, "url": "https://doc.rust-lang.org/book/" }])
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_complexity(pool: PgPool) {
+    // every list's links count, 100 lists of 500 links is too much
+    let response = post(
+        pool,
+        None,
+        json!({ "query": "{ lists(first: 100) { links(first: 500) { url } } }" }),
+    )
+    .await;
+    assert!(response["data"].is_null());
+    assert!(response["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("too complex"));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_batching(pool: PgPool) {
+    let stats = LoaderStats::default();
+    let state = GraphqlState {
+        schema: app::graphql::schema_with_stats(pool, stats.clone()),
+        persisted_queries: None,
+    };
+    let query = "{ lists(first: 10) { links(first: 10) { url project { lists { repo } } } } }";
+    let response = post_to(state, json!({ "query": query })).await;
+    assert!(response["errors"].is_null(), "{response}");
+
+    let links = response["data"]["lists"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|list| list["links"].as_array().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(links.len(), 4);
+    // one query for the links of both lists, one for the projects of all
+    // four links and one for the lists linking to the projects
+    assert_eq!(stats.queries(), 3);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("seed"))]
+async fn test_persisted_queries(pool: PgPool) {
+    let mut persisted_queries = PersistedQueries::default();
+    let hash = persisted_queries.insert(LIST_QUERY);
+    let variables = json!({ "owner": "vinta", "repo": "awesome-python" });
+
+    let response = post(
+        pool.clone(),
+        Some(persisted_queries.clone()),
+        json!({
+            "variables": variables,
+            "extensions": { "persistedQuery": { "version": 1, "sha256Hash": hash } },
+        }),
+    )
+    .await;
+    assert_eq!(response["data"]["list"]["stars"], 180000);
+
+    // the full text of a persisted query is fine too
+    let response = post(
+        pool.clone(),
+        Some(persisted_queries.clone()),
+        json!({ "query": LIST_QUERY, "variables": variables }),
+    )
+    .await;
+    assert_eq!(response["data"]["list"]["repo"], "awesome-python");
+
+    let response = post(
+        pool,
+        Some(persisted_queries),
+        json!({ "query": "{ lists { links { url } } }" }),
+    )
+    .await;
+    assert!(response["data"].is_null());
+    assert_eq!(
+        response["errors"][0]["message"],
+        "only persisted queries are allowed"
+    );
+}
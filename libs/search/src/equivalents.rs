@@ -0,0 +1,195 @@
+use super::{
+    postgres::{fetch_hits, CANDIDATES},
+    reciprocal_rank_fusion, EquivalentsQuery, SearchHit,
+};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Ranks links from other languages by how well their sections and
+/// descriptions match the ones the project is listed with, and by how close
+/// their embeddings are, then fuses both rankings. Projects listed several
+/// times show up once, with their best match.
+pub async fn equivalents(
+    pool: &PgPool,
+    query: &EquivalentsQuery,
+) -> Result<Vec<SearchHit>, sqlx::Error> {
+    let sources = sqlx::query!(
+        r#"--sql
+        SELECT
+            l.id AS "id!",
+            l.description,
+            l.breadcrumbs AS "breadcrumbs!",
+            gp.primary_language,
+            substring(al.repo FROM '^awesome-(.+)$') AS list_language
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+        WHERE
+            lower(rtrim(l.url, '/')) = lower(rtrim($1, '/'))
+        "#,
+        query.url,
+    )
+    .fetch_all(pool)
+    .await?;
+    if sources.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // the project's own language as well as the ones of the lists it is in,
+    // a JavaScript library in awesome-nodejs is no equivalent of itself
+    let own_languages = sources
+        .iter()
+        .flat_map(|s| [s.primary_language.clone(), s.list_language.clone()])
+        .flatten()
+        .map(|l| l.to_lowercase())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let category = sources
+        .iter()
+        .flat_map(|s| s.breadcrumbs.iter().chain(&s.description))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let source_ids = sources.iter().map(|s| s.id).collect::<Vec<_>>();
+
+    let mut rankings = vec![text_ranking(pool, query, &own_languages, &category).await?];
+    if let Some(ranking) = vector_ranking(pool, query, &own_languages, &source_ids).await? {
+        rankings.push(ranking);
+    }
+
+    let fused = reciprocal_rank_fusion(&rankings);
+    let mut seen = HashSet::new();
+    Ok(fetch_hits(pool, fused)
+        .await?
+        .into_iter()
+        .filter(|hit| seen.insert(hit.url.trim_end_matches('/').to_lowercase()))
+        .take(query.limit.max(0) as usize)
+        .collect())
+}
+
+/// Any of the words in the project's sections and descriptions, documents
+/// matching more of them rank higher.
+async fn text_ranking(
+    pool: &PgPool,
+    query: &EquivalentsQuery,
+    own_languages: &[String],
+    category: &str,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"--sql
+        SELECT
+            l.id AS "id!"
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/')),
+            to_tsquery('simple', replace(plainto_tsquery('english', $1)::text, ' & ', ' | ')) q
+        WHERE
+            l.search_vector @@ q
+            AND lower(rtrim(l.url, '/')) <> lower(rtrim($2, '/'))
+            AND CASE WHEN $3::text IS NULL THEN
+                -- NULL for links of unknown language, those can't be ruled out
+                coalesce(lower(coalesce(gp.primary_language, substring(al.repo FROM '^awesome-(.+)$'))) <> ALL ($4), TRUE)
+            ELSE
+                lower(coalesce(gp.primary_language, substring(al.repo FROM '^awesome-(.+)$'))) = lower($3)
+            END
+        ORDER BY
+            ts_rank_cd(l.search_vector, q) DESC
+        LIMIT $5
+        "#,
+        category,
+        query.url,
+        query.language,
+        own_languages,
+        CANDIDATES,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Ranks by similarity to the mean of the project's embeddings, `None` if it
+/// hasn't been embedded yet.
+async fn vector_ranking(
+    pool: &PgPool,
+    query: &EquivalentsQuery,
+    own_languages: &[String],
+    source_ids: &[Uuid],
+) -> Result<Option<Vec<Uuid>>, sqlx::Error> {
+    let source_embeddings = sqlx::query!(
+        r"--sql
+        SELECT
+            model,
+            embedding
+        FROM
+            link_embeddings
+        WHERE
+            awesome_link_id = ANY($1)
+        ",
+        source_ids,
+    )
+    .fetch_all(pool)
+    .await?;
+    let Some(model) = source_embeddings.first().map(|e| e.model.clone()) else {
+        return Ok(None);
+    };
+    let target = mean_embedding(
+        source_embeddings
+            .iter()
+            .filter(|e| e.model == model)
+            .map(|e| e.embedding.as_slice()),
+    );
+
+    let ranking = sqlx::query_scalar!(
+        r#"--sql
+        SELECT
+            l.id AS "id!"
+        FROM
+            current_awesome_links l
+            JOIN link_embeddings e ON e.awesome_link_id = l.id
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+        WHERE
+            e.model = $1
+            AND lower(rtrim(l.url, '/')) <> lower(rtrim($2, '/'))
+            AND CASE WHEN $3::text IS NULL THEN
+                -- NULL for links of unknown language, those can't be ruled out
+                coalesce(lower(coalesce(gp.primary_language, substring(al.repo FROM '^awesome-(.+)$'))) <> ALL ($4), TRUE)
+            ELSE
+                lower(coalesce(gp.primary_language, substring(al.repo FROM '^awesome-(.+)$'))) = lower($3)
+            END
+        ORDER BY
+            (SELECT sum(a * b) FROM unnest(e.embedding, $5::real[]) AS v(a, b)) DESC,
+            l.id
+        LIMIT $6
+        "#,
+        model,
+        query.url,
+        query.language,
+        own_languages,
+        &target,
+        CANDIDATES,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Some(ranking))
+}
+
+/// Averages normalized embeddings and normalizes the result again.
+fn mean_embedding<'a>(embeddings: impl Iterator<Item = &'a [f32]>) -> Vec<f32> {
+    let mut sum: Vec<f32> = vec![];
+    for embedding in embeddings {
+        sum.resize(embedding.len(), 0.0);
+        for (s, x) in sum.iter_mut().zip(embedding) {
+            *s += x;
+        }
+    }
+    let norm = sum.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        sum.iter_mut().for_each(|x| *x /= norm);
+    }
+    sum
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, hash::Hash};
+
+#[cfg(feature = "postgres")]
+mod equivalents;
+#[cfg(feature = "postgres")]
+mod postgres;
+
+#[cfg(feature = "postgres")]
+pub use equivalents::equivalents;
+#[cfg(feature = "postgres")]
+pub use postgres::{facets, search};
+
+/// Constant from the original reciprocal rank fusion paper, it keeps a single
+/// top result in one ranking from outweighing decent ranks in all others.
+const RRF_K: f64 = 60.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// Free text, supports `"quoted phrases"`, `or` and `-negation`
+    pub text: String,
+    /// One of the `AwesomeLinkType` variants, e.g. `Repo`
+    pub link_type: Option<String>,
+    /// Only links from this list, as `owner/repo`
+    pub list: Option<String>,
+    /// Links to projects written in this language, or from `awesome-{language}`
+    pub language: Option<String>,
+    /// Links to projects pushed to since
+    pub pushed_since: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: i64,
+}
+
+/// The query text embedded by the same model as the links.
+#[derive(Debug, Clone)]
+pub struct QueryEmbedding {
+    pub model: String,
+    pub vector: Vec<f32>,
+}
+
+/// Finds projects filling the same role as `url` in other languages, like
+/// the Python equivalent of lodash.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EquivalentsQuery {
+    pub url: String,
+    /// Only projects in this language, otherwise any but the project's own
+    pub language: Option<String>,
+    pub limit: i64,
+}
+
+/// A link matching a search, best matches come first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub url: String,
+    pub title: String,
+    pub breadcrumbs: Vec<String>,
+    pub description: Option<String>,
+    pub link_type: Option<String>,
+    /// The list linking to it, as `owner/repo`
+    pub list: String,
+    /// The project's primary language, or the one its list is about
+    pub language: Option<String>,
+    pub score: f64,
+}
+
+/// A value to narrow a search down to, with the number of matches having it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Facet {
+    pub value: String,
+    pub count: i64,
+}
+
+/// The most common values of each filter among all matches of a search's
+/// text, most common first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Facets {
+    pub link_types: Vec<Facet>,
+    pub lists: Vec<Facet>,
+    pub languages: Vec<Facet>,
+}
+
+/// Merges several rankings of the same items by summing `1 / (k + rank)`, so
+/// scores of incomparable rankers (text relevance, cosine similarity) never
+/// need to be normalized against each other.
+pub fn reciprocal_rank_fusion<T: Clone + Eq + Hash>(rankings: &[Vec<T>]) -> Vec<(T, f64)> {
+    let mut scores: HashMap<T, f64> = HashMap::new();
+    let mut order = Vec::new();
+    for ranking in rankings {
+        for (rank, item) in ranking.iter().enumerate() {
+            let score = scores.entry(item.clone()).or_insert_with(|| {
+                order.push(item.clone());
+                0.0
+            });
+            *score += 1.0 / (RRF_K + rank as f64 + 1.0);
+        }
+    }
+
+    let mut fused = order
+        .into_iter()
+        .map(|item| {
+            let score = scores[&item];
+            (item, score)
+        })
+        .collect::<Vec<_>>();
+    // stable, so ties keep the order of the first ranking
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let fused = reciprocal_rank_fusion(&[vec!["a", "b", "c"], vec!["c", "b"]]);
+        let items = fused.iter().map(|(item, _)| *item).collect::<Vec<_>>();
+
+        // b and c show up in both rankings, c's first place beats b's two seconds
+        assert_eq!(items, ["c", "b", "a"]);
+        assert!((fused[2].1 - 1.0 / 61.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,228 @@
+use super::{reciprocal_rank_fusion, Facet, Facets, QueryEmbedding, SearchHit, SearchQuery};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How many results each ranking contributes before they are fused.
+pub(crate) const CANDIDATES: i64 = 100;
+
+/// Ranks current links by full-text relevance and, given a query embedding,
+/// by similarity to it, then fuses both rankings.
+pub async fn search(
+    pool: &PgPool,
+    query: &SearchQuery,
+    embedding: Option<&QueryEmbedding>,
+) -> Result<Vec<SearchHit>, sqlx::Error> {
+    let mut rankings = vec![text_ranking(pool, query).await?];
+    if let Some(embedding) = embedding {
+        rankings.push(vector_ranking(pool, query, embedding).await?);
+    }
+
+    let fused = reciprocal_rank_fusion(&rankings)
+        .into_iter()
+        .take(query.limit.max(0) as usize)
+        .collect::<Vec<_>>();
+    fetch_hits(pool, fused).await
+}
+
+/// Counts the values of each filter over every text match, ignoring the
+/// query's filters so picking one doesn't hide the others. Keeps the `limit`
+/// most common values per filter.
+pub async fn facets(pool: &PgPool, text: &str, limit: i64) -> Result<Facets, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"--sql
+        WITH matches AS (
+            SELECT
+                l.link_type,
+                al.owner || '/' || al.repo AS list,
+                coalesce(gp.primary_language, substring(al.repo FROM '^awesome-(.+)$')) AS language
+            FROM
+                current_awesome_links l
+                JOIN awesome_lists al ON al.id = l.awesome_list_id
+                LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+            WHERE
+                l.search_vector @@ websearch_to_tsquery('english', $1)
+        ),
+        counts AS (
+            SELECT 'link_type' AS facet, link_type AS value, count(*) AS count FROM matches GROUP BY link_type
+            UNION ALL
+            SELECT 'list', list, count(*) FROM matches GROUP BY list
+            UNION ALL
+            SELECT 'language', language, count(*) FROM matches GROUP BY language
+        )
+        SELECT
+            facet AS "facet!",
+            value AS "value!",
+            count AS "count!"
+        FROM (
+            SELECT
+                *,
+                row_number() OVER (PARTITION BY facet ORDER BY count DESC, value) AS rank
+            FROM
+                counts
+            WHERE
+                value IS NOT NULL
+        ) c
+        WHERE
+            rank <= $2
+        ORDER BY
+            rank
+        "#,
+        text,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut facets = Facets::default();
+    for row in rows {
+        let values = match row.facet.as_str() {
+            "link_type" => &mut facets.link_types,
+            "list" => &mut facets.lists,
+            _ => &mut facets.languages,
+        };
+        values.push(Facet {
+            value: row.value,
+            count: row.count,
+        });
+    }
+    Ok(facets)
+}
+
+/// Looks up the links for fused ids, keeping their order and scores.
+pub(crate) async fn fetch_hits(
+    pool: &PgPool,
+    fused: Vec<(Uuid, f64)>,
+) -> Result<Vec<SearchHit>, sqlx::Error> {
+    let ids = fused.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+
+    let mut hits = sqlx::query!(
+        r#"--sql
+        SELECT
+            l.id AS "id!",
+            l.url AS "url!",
+            l.title,
+            l.description,
+            l.breadcrumbs AS "breadcrumbs!",
+            l.link_type,
+            al.owner,
+            al.repo,
+            coalesce(gp.primary_language, substring(al.repo FROM '^awesome-(.+)$')) AS language
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+        WHERE
+            l.id = ANY($1)
+        "#,
+        &ids,
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| {
+        (
+            r.id,
+            SearchHit {
+                url: r.url,
+                title: r.title.unwrap_or_default(),
+                breadcrumbs: r.breadcrumbs,
+                description: r.description,
+                link_type: r.link_type,
+                list: format!(
+                    "{}/{}",
+                    r.owner.unwrap_or_default(),
+                    r.repo.unwrap_or_default()
+                ),
+                language: r.language,
+                score: 0.0,
+            },
+        )
+    })
+    .collect::<HashMap<_, _>>();
+
+    Ok(fused
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let mut hit = hits.remove(&id)?;
+            hit.score = score;
+            Some(hit)
+        })
+        .collect())
+}
+
+async fn text_ranking(pool: &PgPool, query: &SearchQuery) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"--sql
+        SELECT
+            l.id AS "id!"
+        FROM
+            current_awesome_links l
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/')),
+            websearch_to_tsquery('english', $1) q
+        WHERE
+            l.search_vector @@ q
+            AND ($2::text IS NULL OR lower(l.link_type) = lower($2))
+            AND ($3::text IS NULL OR lower(al.owner || '/' || al.repo) = lower($3))
+            AND ($4::text IS NULL
+                OR lower(gp.primary_language) = lower($4)
+                OR lower(al.repo) = 'awesome-' || lower($4))
+            AND ($5::timestamptz IS NULL OR gp.pushed_at >= $5)
+        ORDER BY
+            ts_rank_cd(l.search_vector, q) DESC
+        LIMIT $6
+        "#,
+        query.text,
+        query.link_type,
+        query.list,
+        query.language,
+        query.pushed_since,
+        CANDIDATES,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Ranks by cosine similarity, the embeddings are normalized so that is their
+/// dot product. The table holds plain arrays so any Postgres will do, which
+/// means there is no ANN index and every embedding of the model is scored,
+/// but only the best candidates leave the database.
+async fn vector_ranking(
+    pool: &PgPool,
+    query: &SearchQuery,
+    embedding: &QueryEmbedding,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"--sql
+        SELECT
+            l.id AS "id!"
+        FROM
+            current_awesome_links l
+            JOIN link_embeddings e ON e.awesome_link_id = l.id
+            JOIN awesome_lists al ON al.id = l.awesome_list_id
+            LEFT JOIN github_projects gp ON lower(gp.url) = lower(rtrim(l.url, '/'))
+        WHERE
+            e.model = $1
+            AND ($2::text IS NULL OR lower(l.link_type) = lower($2))
+            AND ($3::text IS NULL OR lower(al.owner || '/' || al.repo) = lower($3))
+            AND ($4::text IS NULL
+                OR lower(gp.primary_language) = lower($4)
+                OR lower(al.repo) = 'awesome-' || lower($4))
+            AND ($5::timestamptz IS NULL OR gp.pushed_at >= $5)
+        ORDER BY
+            (SELECT sum(a * b) FROM unnest(e.embedding, $6::real[]) AS v(a, b)) DESC,
+            l.id
+        LIMIT $7
+        "#,
+        embedding.model,
+        query.link_type,
+        query.list,
+        query.language,
+        query.pushed_since,
+        &embedding.vector,
+        CANDIDATES,
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,68 @@
+//! Runs the equivalents queries against the seed of the search tests:
+//! `cargo test -p search --features postgres`.
+#![cfg(feature = "postgres")]
+
+use search::{EquivalentsQuery, SearchHit};
+use sqlx::{migrate::Migrator, PgPool};
+
+static MIGRATOR: Migrator = sqlx::migrate!("../../scraper/migrations/postgres");
+
+fn query(url: &str) -> EquivalentsQuery {
+    EquivalentsQuery {
+        url: url.to_string(),
+        language: None,
+        limit: 10,
+    }
+}
+
+fn titles(hits: &[SearchHit]) -> Vec<&str> {
+    hits.iter().map(|hit| hit.title.as_str()).collect()
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("links"))]
+async fn test_equivalents(pool: PgPool) {
+    let hits = search::equivalents(&pool, &query("https://github.com/Tokio-rs/tokio/"))
+        .await
+        .unwrap();
+    let found = titles(&hits);
+    // matches the sections and is the closest embedding
+    assert_eq!(found[0], "aiohttp");
+    // nothing is known about libuv's language, it is kept
+    assert!(found.contains(&"libuv"));
+    // tokio itself and the other Rust links are no equivalents
+    for rust in [
+        "tokio",
+        "serde",
+        "async-std",
+        "The Rust Programming Language",
+    ] {
+        assert!(!found.contains(&rust), "{rust} in {found:?}");
+    }
+
+    let limited = EquivalentsQuery {
+        limit: 1,
+        ..query("https://github.com/tokio-rs/tokio")
+    };
+    let hits = search::equivalents(&pool, &limited).await.unwrap();
+    assert_eq!(titles(&hits), ["aiohttp"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("links"))]
+async fn test_equivalents_in_language(pool: PgPool) {
+    let in_python = EquivalentsQuery {
+        language: Some("Python".to_string()),
+        ..query("https://github.com/tokio-rs/tokio")
+    };
+    let hits = search::equivalents(&pool, &in_python).await.unwrap();
+    let mut found = titles(&hits);
+    found.sort();
+    assert_eq!(found, ["aiohttp", "requests"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("links"))]
+async fn test_equivalents_unknown(pool: PgPool) {
+    let hits = search::equivalents(&pool, &query("https://github.com/nobody/nothing"))
+        .await
+        .unwrap();
+    assert!(hits.is_empty());
+}
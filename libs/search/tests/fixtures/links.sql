@@ -0,0 +1,26 @@
+INSERT INTO awesome_lists(id, url, owner, repo, description, stars, topics, link_count, latest_commit_at, crawled_at)
+  VALUES ('00000000-0000-7000-8000-000000000001', 'https://github.com/rust-unofficial/awesome-rust', 'rust-unofficial', 'awesome-rust', 'A curated list of Rust code and resources.', 40000, '{rust}', 4, '2023-10-01T00:00:00Z', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000002', 'https://github.com/vinta/awesome-python', 'vinta', 'awesome-python', 'An opinionated list of awesome Python frameworks.', 180000, '{python}', 2, '2023-09-01T00:00:00Z', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000003', 'https://github.com/sindresorhus/awesome', 'sindresorhus', 'awesome', 'Awesome lists about all kinds of interesting topics', 300000, '{}', 1, '2023-09-01T00:00:00Z', '2023-10-02T00:00:00Z');
+
+INSERT INTO awesome_links(id, awesome_list_id, url, title, description, breadcrumbs, link_type, removed_at)
+  VALUES ('00000000-0000-7000-8000-000000000011', '00000000-0000-7000-8000-000000000001', 'https://github.com/tokio-rs/tokio', 'tokio', 'A runtime for writing reliable asynchronous applications', '{Libraries,Asynchronous}', 'Repo', NULL),
+  ('00000000-0000-7000-8000-000000000012', '00000000-0000-7000-8000-000000000001', 'https://github.com/serde-rs/serde', 'serde', 'Serialization framework', '{Libraries,Encoding}', 'Repo', NULL),
+  ('00000000-0000-7000-8000-000000000013', '00000000-0000-7000-8000-000000000001', 'https://doc.rust-lang.org/book/', 'The Rust Programming Language', NULL, '{Resources}', 'Book', NULL),
+  ('00000000-0000-7000-8000-000000000014', '00000000-0000-7000-8000-000000000001', 'https://github.com/async-rs/async-std', 'async-std', 'Async version of the Rust standard library', '{Libraries,Asynchronous}', 'Repo', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000021', '00000000-0000-7000-8000-000000000002', 'https://github.com/psf/requests', 'requests', 'HTTP for Humans', '{HTTP}', 'Repo', NULL),
+  ('00000000-0000-7000-8000-000000000022', '00000000-0000-7000-8000-000000000002', 'https://github.com/aio-libs/aiohttp', 'aiohttp', 'Asynchronous HTTP client/server framework', '{Asynchronous}', 'Repo', NULL),
+  ('00000000-0000-7000-8000-000000000031', '00000000-0000-7000-8000-000000000003', 'https://github.com/libuv/libuv', 'libuv', 'Cross-platform asynchronous I/O', '{Platforms}', 'Repo', NULL);
+
+INSERT INTO github_projects(name_with_owner, url, description, stars, forks, primary_language, license, topics, is_archived, is_fork, pushed_at, enriched_at)
+  VALUES ('tokio-rs/tokio', 'https://github.com/tokio-rs/tokio', 'A runtime for writing reliable asynchronous applications with Rust.', 23000, 2100, 'Rust', 'MIT', '{async,rust}', FALSE, FALSE, '2023-10-01T00:00:00Z', '2023-10-02T00:00:00Z'),
+  ('aio-libs/aiohttp', 'https://github.com/aio-libs/aiohttp', 'Asynchronous HTTP client/server framework for asyncio and Python', 14000, 2000, 'Python', 'Apache-2.0', '{asyncio,http}', FALSE, FALSE, '2022-01-01T00:00:00Z', '2023-10-02T00:00:00Z');
+
+-- tiny normalized embeddings, one axis per topic: async, http, encoding
+INSERT INTO link_embeddings(awesome_link_id, model, content_hash, embedding, embedded_at)
+  VALUES ('00000000-0000-7000-8000-000000000011', 'test', '', '{1,0,0}', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000012', 'test', '', '{0,0,1}', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000014', 'test', '', '{1,0,0}', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000021', 'test', '', '{0,1,0}', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000022', 'test', '', '{0.8,0.6,0}', '2023-10-02T00:00:00Z'),
+  ('00000000-0000-7000-8000-000000000031', 'test', '', '{0.6,0,0.8}', '2023-10-02T00:00:00Z');
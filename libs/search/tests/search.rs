@@ -0,0 +1,162 @@
+//! Runs the search queries against a database seeded from `fixtures/links.sql`.
+//! `sqlx::test` creates a fresh database per test on the server at
+//! `DATABASE_URL`: `cargo test -p search --features postgres`.
+#![cfg(feature = "postgres")]
+
+use search::{QueryEmbedding, SearchHit, SearchQuery};
+use sqlx::{migrate::Migrator, PgPool};
+
+static MIGRATOR: Migrator = sqlx::migrate!("../../scraper/migrations/postgres");
+
+fn query(text: &str) -> SearchQuery {
+    SearchQuery {
+        text: text.to_string(),
+        limit: 10,
+        ..Default::default()
+    }
+}
+
+fn embedding(vector: [f32; 3]) -> QueryEmbedding {
+    QueryEmbedding {
+        model: "test".to_string(),
+        vector: vector.to_vec(),
+    }
+}
+
+fn titles(hits: &[SearchHit]) -> Vec<&str> {
+    hits.iter().map(|hit| hit.title.as_str()).collect()
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("links"))]
+async fn test_text_search(pool: PgPool) {
+    let hits = search::search(&pool, &query("asynchronous"), None)
+        .await
+        .unwrap();
+    // async-std was removed from its list
+    let mut found = titles(&hits);
+    found.sort();
+    assert_eq!(found, ["aiohttp", "libuv", "tokio"]);
+
+    let by_language = SearchQuery {
+        language: Some("Python".to_string()),
+        ..query("asynchronous")
+    };
+    let hits = search::search(&pool, &by_language, None).await.unwrap();
+    assert_eq!(titles(&hits), ["aiohttp"]);
+    assert_eq!(hits[0].list, "vinta/awesome-python");
+    assert_eq!(hits[0].language.as_deref(), Some("Python"));
+
+    let by_list = SearchQuery {
+        list: Some("Rust-Unofficial/awesome-rust".to_string()),
+        ..query("asynchronous")
+    };
+    let hits = search::search(&pool, &by_list, None).await.unwrap();
+    assert_eq!(titles(&hits), ["tokio"]);
+
+    let by_type = SearchQuery {
+        link_type: Some("book".to_string()),
+        ..query("rust")
+    };
+    let hits = search::search(&pool, &by_type, None).await.unwrap();
+    assert_eq!(titles(&hits), ["The Rust Programming Language"]);
+    // no project to take it from, the language is the one of the list
+    assert_eq!(hits[0].language.as_deref(), Some("rust"));
+
+    let by_push = SearchQuery {
+        pushed_since: Some("2023-01-01T00:00:00Z".parse().unwrap()),
+        ..query("asynchronous")
+    };
+    let hits = search::search(&pool, &by_push, None).await.unwrap();
+    assert_eq!(titles(&hits), ["tokio"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("links"))]
+async fn test_vector_search(pool: PgPool) {
+    let async_embedding = embedding([1.0, 0.0, 0.0]);
+    let hits = search::search(
+        &pool,
+        &query("nothing matches this"),
+        Some(&async_embedding),
+    )
+    .await
+    .unwrap();
+    assert_eq!(titles(&hits)[..2], ["tokio", "aiohttp"]);
+    assert!(!titles(&hits).contains(&"async-std"));
+
+    let by_language = SearchQuery {
+        language: Some("python".to_string()),
+        ..query("nothing matches this")
+    };
+    let hits = search::search(&pool, &by_language, Some(&async_embedding))
+        .await
+        .unwrap();
+    assert_eq!(titles(&hits), ["aiohttp", "requests"]);
+
+    let other_model = QueryEmbedding {
+        model: "other".to_string(),
+        ..async_embedding
+    };
+    let hits = search::search(&pool, &query("nothing matches this"), Some(&other_model))
+        .await
+        .unwrap();
+    assert!(hits.is_empty());
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("links"))]
+async fn test_hybrid_search(pool: PgPool) {
+    let http_embedding = embedding([0.0, 1.0, 0.0]);
+    let hits = search::search(&pool, &query("http"), Some(&http_embedding))
+        .await
+        .unwrap();
+    // both match the text, requests is also the closest embedding
+    assert_eq!(titles(&hits)[..2], ["requests", "aiohttp"]);
+    // only similar, a single ranking puts it behind the ones in both
+    assert!(hits[1].score > hits[2].score);
+
+    let limited = SearchQuery {
+        limit: 1,
+        ..query("http")
+    };
+    let hits = search::search(&pool, &limited, Some(&http_embedding))
+        .await
+        .unwrap();
+    assert_eq!(titles(&hits), ["requests"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("links"))]
+async fn test_facets(pool: PgPool) {
+    let facets = search::facets(&pool, "asynchronous", 10).await.unwrap();
+    let facet = |values: &[search::Facet]| {
+        values
+            .iter()
+            .map(|f| (f.value.clone(), f.count))
+            .collect::<Vec<_>>()
+    };
+    // over every match, async-std was removed
+    assert_eq!(facet(&facets.link_types), [("Repo".to_string(), 3)]);
+    assert_eq!(
+        facet(&facets.lists),
+        [
+            ("rust-unofficial/awesome-rust".to_string(), 1),
+            ("sindresorhus/awesome".to_string(), 1),
+            ("vinta/awesome-python".to_string(), 1),
+        ]
+    );
+    // libuv has neither a project nor a language list
+    assert_eq!(
+        facet(&facets.languages),
+        [("Python".to_string(), 1), ("Rust".to_string(), 1)]
+    );
+
+    let facets = search::facets(&pool, "rust", 1).await.unwrap();
+    assert_eq!(facet(&facets.link_types), [("Book".to_string(), 1)]);
+    assert_eq!(
+        facet(&facets.lists),
+        [("rust-unofficial/awesome-rust".to_string(), 1)]
+    );
+
+    let facets = search::facets(&pool, "nothing matches this", 10)
+        .await
+        .unwrap();
+    assert_eq!(facets, search::Facets::default());
+}
@@ -0,0 +1,5 @@
+// `sqlx::migrate!` embeds the migrations at compile time
+fn main() {
+    // trigger recompilation when a new migration is added
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -1,6 +1,3 @@
--- DROP TABLE IF EXISTS awesome_projects CASCADE;
--- DROP TABLE IF EXISTS awesome_lists CASCADE;
--- DROP TABLE IF EXISTS awesome_links CASCADE;
 CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 
 
@@ -88,4 +85,5 @@ CREATE TABLE IF NOT EXISTS awesome_links(
   title text,
   description text,
   breadcrumbs text ARRAY NOT NULL,
-  UNIQUE (awesome_list_id, url))
+  UNIQUE (awesome_list_id, url)
+);
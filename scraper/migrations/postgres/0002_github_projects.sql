@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS github_projects(
+  id uuid PRIMARY KEY DEFAULT _uuid_generate_v7(),
+  name_with_owner text NOT NULL,
+  url text NOT NULL,
+  description text,
+  homepage_url text,
+  stars integer NOT NULL,
+  forks integer NOT NULL,
+  primary_language text,
+  license text,
+  topics text ARRAY NOT NULL,
+  is_archived boolean NOT NULL,
+  is_fork boolean NOT NULL,
+  pushed_at timestamp with time zone,
+  enriched_at timestamp with time zone NOT NULL,
+  UNIQUE (name_with_owner)
+);
+
+CREATE TABLE IF NOT EXISTS github_repo_aliases(
+  alias text PRIMARY KEY,
+  name_with_owner text NOT NULL
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS repo_snapshots(
+  id uuid PRIMARY KEY DEFAULT _uuid_generate_v7(),
+  name_with_owner text NOT NULL,
+  taken_at timestamp with time zone NOT NULL,
+  stars integer NOT NULL,
+  forks integer NOT NULL,
+  open_issues integer NOT NULL,
+  commits integer,
+  UNIQUE (name_with_owner, taken_at)
+);
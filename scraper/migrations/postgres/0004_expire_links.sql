@@ -0,0 +1,12 @@
+ALTER TABLE awesome_links
+  ADD COLUMN IF NOT EXISTS removed_at timestamp with time zone;
+
+-- links still present in the latest crawl of their list, which is what the app
+-- should be reading from
+CREATE OR REPLACE VIEW current_awesome_links AS
+SELECT
+  *
+FROM
+  awesome_links
+WHERE
+  removed_at IS NULL;
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS awesome_link_events(
+  id uuid PRIMARY KEY DEFAULT _uuid_generate_v7(),
+  awesome_link_id uuid NOT NULL REFERENCES awesome_links(id),
+  kind text NOT NULL CHECK (kind IN ('added', 'removed', 'moved', 'description_changed')),
+  occurred_at timestamp with time zone NOT NULL,
+  old_breadcrumbs text ARRAY,
+  new_breadcrumbs text ARRAY,
+  old_description text,
+  new_description text
+);
+
+CREATE INDEX IF NOT EXISTS awesome_link_events_occurred_at_idx ON awesome_link_events(occurred_at DESC);
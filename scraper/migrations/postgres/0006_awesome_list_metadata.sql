@@ -0,0 +1,13 @@
+ALTER TABLE awesome_lists
+  ADD COLUMN IF NOT EXISTS owner text,
+  ADD COLUMN IF NOT EXISTS repo text,
+  ADD COLUMN IF NOT EXISTS description text,
+  ADD COLUMN IF NOT EXISTS stars integer,
+  ADD COLUMN IF NOT EXISTS homepage_url text,
+  ADD COLUMN IF NOT EXISTS topics text ARRAY NOT NULL DEFAULT '{}',
+  ADD COLUMN IF NOT EXISTS default_branch text,
+  ADD COLUMN IF NOT EXISTS readme_path text,
+  ADD COLUMN IF NOT EXISTS link_count integer NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS parent_url text;
+
+CREATE INDEX IF NOT EXISTS awesome_lists_owner_repo_idx ON awesome_lists(lower(owner), lower(repo));
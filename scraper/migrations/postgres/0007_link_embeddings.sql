@@ -0,0 +1,9 @@
+-- plain arrays rather than pgvector so any Postgres will do, the embeddings
+-- are ranked by a dot product in SQL without an ANN index
+CREATE TABLE IF NOT EXISTS link_embeddings(
+  awesome_link_id uuid PRIMARY KEY REFERENCES awesome_links(id) ON DELETE CASCADE,
+  model text NOT NULL,
+  content_hash text NOT NULL,
+  embedding real ARRAY NOT NULL,
+  embedded_at timestamp with time zone NOT NULL
+);
@@ -0,0 +1,26 @@
+ALTER TABLE awesome_links
+  ADD COLUMN IF NOT EXISTS link_type text;
+
+-- array_to_string is only stable, generated columns need immutable functions
+CREATE OR REPLACE FUNCTION _breadcrumbs_text(breadcrumbs text[])
+  RETURNS text
+  AS $$
+  SELECT
+    array_to_string(breadcrumbs, ' ')
+$$
+LANGUAGE sql
+IMMUTABLE;
+
+ALTER TABLE awesome_links
+  ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (setweight(to_tsvector('english', coalesce(title, '')), 'A') || setweight(to_tsvector('english', coalesce(description, '')), 'B') || setweight(to_tsvector('english', _breadcrumbs_text(breadcrumbs)), 'C')) STORED;
+
+CREATE INDEX IF NOT EXISTS awesome_links_search_idx ON awesome_links USING GIN (search_vector);
+
+-- pick up the new columns
+CREATE OR REPLACE VIEW current_awesome_links AS
+SELECT
+  *
+FROM
+  awesome_links
+WHERE
+  removed_at IS NULL;
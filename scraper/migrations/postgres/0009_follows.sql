@@ -0,0 +1,12 @@
+-- there are no accounts yet, the follower id is handed out in a cookie
+CREATE TABLE IF NOT EXISTS follows(
+  id uuid PRIMARY KEY DEFAULT _uuid_generate_v7(),
+  follower_id uuid NOT NULL,
+  kind text NOT NULL CHECK (kind IN ('list', 'category', 'project')),
+  -- `owner/repo` of the list for lists and categories, the url for projects
+  target text NOT NULL,
+  -- the category and everything below it
+  breadcrumbs text ARRAY NOT NULL DEFAULT '{}',
+  created_at timestamp with time zone NOT NULL DEFAULT now(),
+  UNIQUE (follower_id, kind, target, breadcrumbs)
+);
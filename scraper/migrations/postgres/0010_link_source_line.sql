@@ -0,0 +1,15 @@
+-- where a link starts in its list's README, so lists can be shown in README
+-- order. NULL until the list is ingested again.
+ALTER TABLE awesome_links
+  ADD COLUMN IF NOT EXISTS source_line integer;
+
+CREATE INDEX IF NOT EXISTS awesome_links_source_line_idx ON awesome_links(awesome_list_id, source_line);
+
+-- pick up the new column
+CREATE OR REPLACE VIEW current_awesome_links AS
+SELECT
+  *
+FROM
+  awesome_links
+WHERE
+  removed_at IS NULL;
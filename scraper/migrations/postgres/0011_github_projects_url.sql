@@ -0,0 +1,3 @@
+-- links are matched to their project with
+-- `lower(gp.url) = lower(rtrim(l.url, '/'))`
+CREATE INDEX IF NOT EXISTS github_projects_lower_url_idx ON github_projects(lower(url));
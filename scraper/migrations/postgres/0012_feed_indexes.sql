@@ -0,0 +1,6 @@
+-- the feed starts from the followed links and looks up their events
+CREATE INDEX IF NOT EXISTS awesome_link_events_link_idx ON awesome_link_events(awesome_link_id, occurred_at DESC);
+
+-- projects are followed by url, matched with
+-- `lower(rtrim(l.url, '/')) = lower(rtrim(f.target, '/'))`
+CREATE INDEX IF NOT EXISTS awesome_links_lower_url_idx ON awesome_links(lower(rtrim(url, '/')));
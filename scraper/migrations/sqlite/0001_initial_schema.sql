@@ -0,0 +1,87 @@
+-- Mirrors the Postgres schema. Arrays are stored as JSON text and timestamps
+-- as RFC 3339 text, ids are random hex instead of UUIDv7.
+CREATE TABLE IF NOT EXISTS awesome_lists(
+  id text PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+  url text NOT NULL,
+  latest_commit_at text NOT NULL,
+  crawled_at text NOT NULL,
+  owner text,
+  repo text,
+  description text,
+  stars integer,
+  homepage_url text,
+  topics text NOT NULL DEFAULT '[]',
+  default_branch text,
+  readme_path text,
+  link_count integer NOT NULL DEFAULT 0,
+  parent_url text,
+  UNIQUE (url)
+);
+
+CREATE INDEX IF NOT EXISTS awesome_lists_owner_repo_idx ON awesome_lists(lower(owner), lower(repo));
+
+CREATE TABLE IF NOT EXISTS awesome_links(
+  id text PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+  awesome_list_id text NOT NULL REFERENCES awesome_lists(id),
+  url text NOT NULL,
+  title text,
+  description text,
+  breadcrumbs text NOT NULL,
+  removed_at text,
+  UNIQUE (awesome_list_id, url)
+);
+
+CREATE VIEW IF NOT EXISTS current_awesome_links AS
+SELECT
+  *
+FROM
+  awesome_links
+WHERE
+  removed_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS awesome_link_events(
+  id text PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+  awesome_link_id text NOT NULL REFERENCES awesome_links(id),
+  kind text NOT NULL CHECK (kind IN ('added', 'removed', 'moved', 'description_changed')),
+  occurred_at text NOT NULL,
+  old_breadcrumbs text,
+  new_breadcrumbs text,
+  old_description text,
+  new_description text
+);
+
+CREATE INDEX IF NOT EXISTS awesome_link_events_occurred_at_idx ON awesome_link_events(occurred_at DESC);
+
+CREATE TABLE IF NOT EXISTS github_projects(
+  id text PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+  name_with_owner text NOT NULL,
+  url text NOT NULL,
+  description text,
+  homepage_url text,
+  stars integer NOT NULL,
+  forks integer NOT NULL,
+  primary_language text,
+  license text,
+  topics text NOT NULL,
+  is_archived boolean NOT NULL,
+  is_fork boolean NOT NULL,
+  pushed_at text,
+  enriched_at text NOT NULL,
+  UNIQUE (name_with_owner)
+);
+
+CREATE TABLE IF NOT EXISTS github_repo_aliases(
+  alias text PRIMARY KEY,
+  name_with_owner text NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS repo_snapshots(
+  id text PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+  name_with_owner text NOT NULL,
+  taken_at text NOT NULL,
+  stars integer NOT NULL,
+  forks integer NOT NULL,
+  open_issues integer NOT NULL,
+  commits integer,
+  UNIQUE (name_with_owner, taken_at)
+);
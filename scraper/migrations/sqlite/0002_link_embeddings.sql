@@ -0,0 +1,8 @@
+-- embeddings are stored as little-endian f32 blobs
+CREATE TABLE IF NOT EXISTS link_embeddings(
+  awesome_link_id text PRIMARY KEY REFERENCES awesome_links(id) ON DELETE CASCADE,
+  model text NOT NULL,
+  content_hash text NOT NULL,
+  embedding blob NOT NULL,
+  embedded_at text NOT NULL
+);
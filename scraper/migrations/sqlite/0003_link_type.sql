@@ -0,0 +1 @@
+ALTER TABLE awesome_links ADD COLUMN link_type text;
@@ -0,0 +1,3 @@
+ALTER TABLE awesome_links ADD COLUMN source_line integer;
+
+CREATE INDEX IF NOT EXISTS awesome_links_source_line_idx ON awesome_links(awesome_list_id, source_line);
@@ -22,19 +22,28 @@ pub struct AwesomeLink {
 
 impl AwesomeLink {
     pub fn as_github_repo(&self) -> Option<(&str, &str)> {
-        if self.url.domain() == Some("github.com") && self.link_type == AwesomeLinkType::Repo {
-            let path = self.url.path();
-            let mut path = path.split('/').skip(1);
-            let owner = path.next()?;
-            let repo = path.next()?;
-            Some((owner, repo))
+        if self.link_type == AwesomeLinkType::Repo {
+            github_repo(&self.url)
         } else {
             None
         }
     }
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd)]
+/// Splits a `https://github.com/{owner}/{repo}/...` url into its owner and repo.
+pub fn github_repo(url: &Url) -> Option<(&str, &str)> {
+    if url.domain() != Some("github.com") {
+        return None;
+    }
+    let mut path = url.path().split('/').skip(1);
+    let owner = path.next().filter(|o| !o.is_empty())?;
+    let repo = path.next().filter(|r| !r.is_empty())?;
+    Some((owner, repo))
+}
+
+#[derive(
+    Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, PartialOrd, clap::ValueEnum,
+)]
 pub enum AwesomeLinkType {
     Repo,
     Article,
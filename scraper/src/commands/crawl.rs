@@ -1,9 +1,11 @@
 use crate::awesome_links::{extract_awesome_links, AwesomeLink};
 use crate::github;
+use crate::snapshots::RepoMetrics;
 
 use kdam::{term, term::Colorizer, tqdm, BarExt, Column, RichProgress, Spinner};
+use std::borrow::Cow;
 use std::collections::HashSet;
-use std::collections::{vec_deque, BTreeSet};
+use std::collections::{vec_deque, BTreeMap, BTreeSet};
 use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::path::PathBuf;
@@ -35,6 +37,8 @@ pub struct CrawlArgs {
 pub struct CrawledRepoData {
     pub crawled_at: chrono::DateTime<chrono::Utc>,
     pub repo_view: github::repo_view::ResponseData,
+    #[serde(default)]
+    pub readme_path: Option<String>,
     pub readme: github::repo_file_with_history::ResponseData,
 }
 
@@ -45,9 +49,62 @@ pub struct CrawledAwesomeList {
     pub description: Option<String>,
     pub crawled_at: chrono::DateTime<chrono::Utc>,
     pub latest_commit_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub metrics: Option<RepoMetrics>,
+    #[serde(default)]
+    pub homepage_url: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub readme_path: Option<String>,
+    /// The `owner/repo` of the list this one was discovered from
+    #[serde(default)]
+    pub parent: Option<String>,
     pub awesome_links: Vec<AwesomeLink>,
 }
 
+/// Maps lowercased `owner/repo` names that were renamed or transferred to
+/// the canonical `owner/repo` GitHub knows them by today.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(transparent)]
+pub struct RepoAliases(BTreeMap<String, String>);
+
+impl RepoAliases {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let rdr = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(rdr)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn insert(&mut self, alias: (&str, &str), canonical: (&str, &str)) {
+        let (owner, repo) = alias;
+        let alias = f!("{owner}/{repo}").to_lowercase();
+        let (owner, repo) = canonical;
+        let canonical = f!("{owner}/{repo}");
+        if alias != canonical.to_lowercase() {
+            self.0.insert(alias, canonical);
+        }
+    }
+
+    pub fn canonical(&self, owner: &str, repo: &str) -> (String, String) {
+        self.0
+            .get(&f!("{owner}/{repo}").to_lowercase())
+            .and_then(|c| c.split_once('/'))
+            .map(|(owner, repo)| (owner.to_string(), repo.to_string()))
+            .unwrap_or_else(|| (owner.to_string(), repo.to_string()))
+    }
+}
+
 impl CrawlArgs {
     pub async fn run(&self) -> Result<()> {
         let cache_path = self.output_path.join("cache");
@@ -58,31 +115,43 @@ impl CrawlArgs {
         let awesome_lists_path = self.output_path.join("awesome_lists");
         fs::create_dir_all(&awesome_lists_path)?;
 
-        let client = Client::builder()
-            .user_agent("graphql-rust/0.9.0")
-            .default_headers(
-                std::iter::once((
-                    reqwest::header::AUTHORIZATION,
-                    reqwest::header::HeaderValue::from_str(&format!(
-                        "Bearer {}",
-                        &self.github_token
-                    ))
-                    .unwrap(),
-                ))
-                .collect(),
-            )
-            .build()?;
-        let mut to_scrape =
-            VecDeque::from([(String::from("sindresorhus"), String::from("awesome"), true)]);
+        let client = github::client(&self.github_token)?;
+        let mut to_scrape = VecDeque::from([(
+            String::from("sindresorhus"),
+            String::from("awesome"),
+            true,
+            None::<String>,
+        )]);
+
+        let aliases_path = self.output_path.join("aliases.json");
+        let mut aliases = RepoAliases::load(&aliases_path)?;
 
         let mut processed = HashSet::<String>::new();
+        let mut crawled = HashSet::<String>::new();
 
         //TODO: add caching and re-fetching
         let mut pb = tqdm!();
         pb.write("Fetching awesome lists...".colorize("bold blue"))?;
-        while let Some((owner, repo, follow_links)) = to_scrape.pop_front() {
+        while let Some((owner, repo, follow_links, parent)) = to_scrape.pop_front() {
             pb.update(1)?;
-            let repo_data = match fetch_with_cache(&owner, &repo, &client, &cache_path).await {
+            let repo_data =
+                match fetch_canonical(&owner, &repo, &client, &cache_path, &mut aliases).await {
+                    Ok(data) => data,
+                    Err(e) => {
+                        pb.write(format!(
+                            "{}: {}",
+                            f!("{owner}/{repo}").colorize("bold red"),
+                            e
+                        ))?;
+                        continue;
+                    }
+                };
+            let (owner, repo) = repo_data.canonical_name(&owner, &repo);
+            if !crawled.insert(f!("{owner}/{repo}").to_lowercase()) {
+                // linked under more than one name
+                continue;
+            }
+            let (readme_content, latest_commit_at) = match repo_data.readme() {
                 Ok(data) => data,
                 Err(e) => {
                     pb.write(format!(
@@ -93,24 +162,36 @@ impl CrawlArgs {
                     continue;
                 }
             };
-            let readme_content = repo_data.readme.file_content()?;
             let links = extract_awesome_links(&readme_content)?;
 
             let md_path = md_path.join(f!("{owner}-{repo}.md"));
             let mut output_file = std::fs::File::create(md_path)?;
             output_file.write_all(readme_content.as_bytes())?;
 
+            let repository = repo_data.repo_view.repository.as_ref();
             let awesome_list = CrawledAwesomeList {
                 owner: owner.clone(),
                 repo: repo.clone(),
                 awesome_links: links,
-                description: repo_data
-                    .repo_view
-                    .repository
-                    .as_ref()
-                    .and_then(|r| r.description.clone()),
+                description: repository.and_then(|r| r.description.clone()),
                 crawled_at: repo_data.crawled_at.clone(),
-                latest_commit_at: repo_data.readme.latest_commit_date()?.clone(),
+                latest_commit_at: *latest_commit_at,
+                metrics: repository.map(RepoMetrics::from),
+                homepage_url: repository
+                    .and_then(|r| r.homepage_url.clone())
+                    .filter(|url| !url.is_empty()),
+                topics: repository
+                    .and_then(|r| r.repository_topics.nodes.as_ref())
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|node| node.topic.name.clone())
+                    .collect(),
+                default_branch: repository
+                    .and_then(|r| r.default_branch_ref.as_ref())
+                    .map(|r| r.name.clone()),
+                readme_path: repo_data.readme_path.clone(),
+                parent,
             };
             let list_path = awesome_lists_path.join(f!("{owner}-{repo}.json"));
             let output_file = std::fs::File::create(list_path)?;
@@ -122,11 +203,14 @@ impl CrawlArgs {
                     .awesome_links
                     .into_iter()
                     .flat_map(|link| match link.as_github_repo() {
-                        Some((owner, repo)) => Some((owner.to_string(), repo.to_string(), false)),
+                        Some((link_owner, link_repo)) => {
+                            let (link_owner, link_repo) = aliases.canonical(link_owner, link_repo);
+                            Some((link_owner, link_repo, false, Some(f!("{owner}/{repo}"))))
+                        }
                         _ => None,
                     })
-                    .filter(|(owner, repo, _)| {
-                        let cache_key = f!("{owner}/{repo}");
+                    .filter(|(owner, repo, _, _)| {
+                        let cache_key = f!("{owner}/{repo}").to_lowercase();
                         let exists = processed.contains(&cache_key);
                         if !exists {
                             processed.insert(cache_key);
@@ -141,10 +225,112 @@ impl CrawlArgs {
             }
         }
 
+        aliases.save(&aliases_path)?;
+
         Ok(())
     }
 }
 
+impl CrawledRepoData {
+    /// The `owner/repo` GitHub reports for the repository, falling back to
+    /// the name it was requested by.
+    pub fn canonical_name(&self, owner: &str, repo: &str) -> (String, String) {
+        self.repo_view
+            .repository
+            .as_ref()
+            .and_then(|r| r.name_with_owner.split_once('/'))
+            .map(|(owner, repo)| (owner.to_string(), repo.to_string()))
+            .unwrap_or_else(|| (owner.to_string(), repo.to_string()))
+    }
+
+    /// The readme's content and when it was last committed to.
+    pub fn readme(&self) -> github::Result<(Cow<'_, str>, &github::DateTime)> {
+        let content = self.readme.file_content()?;
+        let latest_commit_at = self.readme.latest_commit_date()?;
+        Ok((content, latest_commit_at))
+    }
+}
+
+/// Fetches a repository by the name it is currently known as, following
+/// renames and transfers and recording any alias along the way.
+async fn fetch_canonical(
+    owner: &str,
+    repo: &str,
+    client: &Client,
+    cache_path: &Path,
+    aliases: &mut RepoAliases,
+) -> Result<CrawledRepoData> {
+    let (owner, repo) = aliases.canonical(owner, repo);
+
+    let data = match fetch_with_cache(&owner, &repo, client, cache_path).await {
+        Err(e)
+            if matches!(
+                e.downcast_ref(),
+                Some(github::GithubError::MissingRepository)
+            ) =>
+        {
+            let Some((new_owner, new_repo)) =
+                github::resolve_redirect(&github::GITHUB_URL, &owner, &repo).await?
+            else {
+                return Err(e);
+            };
+            aliases.insert((&owner, &repo), (&new_owner, &new_repo));
+            fetch_with_cache(&new_owner, &new_repo, client, cache_path).await?
+        }
+        other => other?,
+    };
+
+    // the API can also quietly resolve the old name for us, in which case we
+    // move the cached response over to where we'll look for it next time
+    let (canonical_owner, canonical_repo) = data.canonical_name(&owner, &repo);
+    aliases.insert((&owner, &repo), (&canonical_owner, &canonical_repo));
+    let cache_key = cache_path.join(f!("{owner}-{repo}.json"));
+    let canonical_key = cache_path.join(f!("{canonical_owner}-{canonical_repo}.json"));
+    if cache_key != canonical_key && cache_key.exists() {
+        if canonical_key.exists() {
+            fs::remove_file(&cache_key)?;
+        } else {
+            fs::rename(&cache_key, &canonical_key)?;
+        }
+    }
+
+    Ok(data)
+}
+
+/// Follows a README symlink one request per link, the root listing only
+/// tells which entries are symlinks.
+async fn readme_path(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    root_files: &github::repo_view::RepoViewRepositoryRootFiles,
+) -> Result<String> {
+    let mut lookup = root_files.find_readme()?;
+    for _ in 0..github::MAX_SYMLINK_DEPTH {
+        let link = match lookup {
+            github::ReadmeLookup::Found(path) => return Ok(path),
+            github::ReadmeLookup::Symlink(link) => link,
+        };
+        let target = post_graphql::<github::SymlinkTarget, _>(
+            client,
+            github::GRAPHQL_URL,
+            github::symlink_target::Variables {
+                owner: owner.to_string(),
+                name: repo.to_string(),
+                expression: f!("HEAD:{link}"),
+            },
+        )
+        .await?
+        .data
+        .context("missing symlink response data")?;
+        lookup = root_files.follow_symlink(&link, target.target())?;
+    }
+    match lookup {
+        github::ReadmeLookup::Found(path) => Ok(path),
+        github::ReadmeLookup::Symlink(link) => Err(github::GithubError::BrokenSymlink(link).into()),
+    }
+}
+
 async fn fetch_with_cache(
     owner: &str,
     repo: &str,
@@ -162,12 +348,16 @@ async fn fetch_with_cache(
     };
 
     if cache_key.exists() {
-        return read_cached();
+        match read_cached() {
+            Ok(out) => return Ok(out),
+            // written by an older version of the queries, so we just fetch it again
+            Err(_) => fs::remove_file(&cache_key)?,
+        }
     }
 
     let root_data = post_graphql::<github::RepoView, _>(
         &client,
-        "https://api.github.com/graphql",
+        github::GRAPHQL_URL,
         github::repo_view::Variables {
             name: repo.to_string(),
             owner: owner.to_string(),
@@ -180,20 +370,27 @@ async fn fetch_with_cache(
     let root_repo = root_data
         .repository
         .as_ref()
-        .context("missing repository")?;
+        .ok_or(github::GithubError::MissingRepository)?;
+    let (owner, repo) = root_repo
+        .name_with_owner
+        .split_once('/')
+        .unwrap_or((owner, repo));
+    if root_repo.is_empty {
+        return Err(github::GithubError::EmptyRepository.into());
+    }
     let root_files = root_repo
         .root_files
         .as_ref()
-        .context("missing root files")?;
-    let readme_path = root_files.find_readme_path()?.context("no root readme")?;
+        .ok_or(github::GithubError::MissingField("root files"))?;
+    let readme_path = readme_path(client, owner, repo, root_files).await?;
 
     let readme_data: github::repo_file_with_history::ResponseData =
         post_graphql::<github::RepoFileWithHistory, _>(
             &client,
-            "https://api.github.com/graphql",
+            github::GRAPHQL_URL,
             github::repo_file_with_history::Variables {
                 ref_filename: f!("HEAD:{readme_path}"),
-                filename: readme_path,
+                filename: readme_path.clone(),
                 name: repo.to_string(),
                 owner: owner.to_string(),
             },
@@ -205,6 +402,7 @@ async fn fetch_with_cache(
     let out = CrawledRepoData {
         crawled_at: chrono::Utc::now(),
         repo_view: root_data,
+        readme_path: Some(readme_path),
         readme: readme_data,
     };
 
@@ -217,3 +415,99 @@ async fn fetch_with_cache(
         return read_cached();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Caches a response for `owner/repo` that GitHub answered as
+    /// `name_with_owner`, the way it does for renamed repositories.
+    fn cache(cache_path: &Path, owner: &str, repo: &str, name_with_owner: &str) {
+        let data = serde_json::json!({
+            "crawled_at": "2023-10-01T00:00:00Z",
+            "repo_view": {
+                "repository": {
+                    "nameWithOwner": name_with_owner,
+                    "url": f!("https://github.com/{name_with_owner}"),
+                    "isEmpty": false,
+                    "stargazerCount": 1,
+                    "forkCount": 0,
+                    "repositoryTopics": { "nodes": [] },
+                    "openIssues": { "totalCount": 0 },
+                    "closedIssues": { "totalCount": 0 },
+                }
+            },
+            "readme": { "repository": null },
+        });
+        let file = File::create(cache_path.join(f!("{owner}-{repo}.json"))).unwrap();
+        serde_json::to_writer(file, &data).unwrap();
+    }
+
+    #[test]
+    fn test_repo_aliases() {
+        let mut aliases = RepoAliases::default();
+        aliases.insert(("Old-Owner", "old-repo"), ("new-owner", "new-repo"));
+        // only the case changed, GitHub resolves that without help
+        aliases.insert(("same", "Repo"), ("Same", "repo"));
+
+        assert_eq!(
+            aliases.canonical("old-owner", "OLD-REPO"),
+            (String::from("new-owner"), String::from("new-repo"))
+        );
+        assert_eq!(
+            aliases.canonical("same", "Repo"),
+            (String::from("same"), String::from("Repo"))
+        );
+
+        let path = std::env::temp_dir().join(f!("epiclist-aliases-{}.json", std::process::id()));
+        aliases.save(&path).unwrap();
+        let loaded = RepoAliases::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.0, aliases.0);
+        assert!(RepoAliases::load(&path).unwrap().0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_canonical_moves_cache() {
+        let cache_path = std::env::temp_dir().join(f!("epiclist-crawl-{}", std::process::id()));
+        fs::create_dir_all(&cache_path).unwrap();
+        let client = github::client("token").unwrap();
+        let mut aliases = RepoAliases::default();
+
+        cache(&cache_path, "old-owner", "old-repo", "new-owner/new-repo");
+        let data = fetch_canonical("old-owner", "old-repo", &client, &cache_path, &mut aliases)
+            .await
+            .unwrap();
+        assert_eq!(
+            data.canonical_name("old-owner", "old-repo"),
+            (String::from("new-owner"), String::from("new-repo"))
+        );
+        assert_eq!(
+            aliases.canonical("old-owner", "old-repo"),
+            (String::from("new-owner"), String::from("new-repo"))
+        );
+        assert!(!cache_path.join("old-owner-old-repo.json").exists());
+        assert!(cache_path.join("new-owner-new-repo.json").exists());
+
+        // the alias now leads straight to the moved response
+        fetch_canonical("old-owner", "old-repo", &client, &cache_path, &mut aliases)
+            .await
+            .unwrap();
+
+        // a stale response under another old name is dropped, not moved over
+        cache(&cache_path, "older-owner", "old-repo", "new-owner/new-repo");
+        fetch_canonical(
+            "older-owner",
+            "old-repo",
+            &client,
+            &cache_path,
+            &mut aliases,
+        )
+        .await
+        .unwrap();
+        assert!(!cache_path.join("older-owner-old-repo.json").exists());
+        assert!(cache_path.join("new-owner-new-repo.json").exists());
+
+        fs::remove_dir_all(&cache_path).unwrap();
+    }
+}
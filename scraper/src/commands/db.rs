@@ -0,0 +1,79 @@
+use crate::store::{self, MigrationStatus};
+use anyhow::{bail, Result};
+use clap::{Args, Subcommand};
+use kdam::term::Colorizer;
+
+#[derive(Debug, Args)]
+pub struct DbArgs {
+    #[clap(long, env)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: DbCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum DbCommand {
+    /// Apply any pending migrations
+    Migrate,
+
+    /// List migrations and whether they have been applied
+    Status,
+
+    /// Mark the migrations up to VERSION as applied without running them,
+    /// for databases created from the old `schemas/db.sql`
+    Baseline {
+        #[clap(default_value_t = 1)]
+        version: i64,
+    },
+
+    /// Drop everything and migrate from scratch
+    Reset {
+        /// Confirm that all data may be deleted
+        #[clap(long)]
+        yes: bool,
+    },
+}
+
+impl DbArgs {
+    pub async fn run(&self) -> Result<()> {
+        let store = store::connect(&self.database_url, 1).await?;
+
+        match &self.command {
+            DbCommand::Migrate => {
+                store.migrate().await?;
+                println!("{}", "database is up to date".colorize("bold green"));
+            }
+            DbCommand::Status => {
+                for migration in store.migrations().await? {
+                    let status = match migration.status {
+                        MigrationStatus::Applied => "applied".colorize("green"),
+                        MigrationStatus::Pending => "pending".colorize("yellow"),
+                        MigrationStatus::Modified => "modified".colorize("bold red"),
+                    };
+                    println!(
+                        "{:>4} {:<30} {}",
+                        migration.version, migration.description, status
+                    );
+                }
+            }
+            DbCommand::Baseline { version } => {
+                let known = store.migrations().await?;
+                if !known.iter().any(|m| m.version == *version) {
+                    bail!("there is no migration {version}");
+                }
+                let recorded = store.baseline(*version).await?;
+                println!("marked {recorded} migration(s) as applied");
+            }
+            DbCommand::Reset { yes } => {
+                if !yes {
+                    bail!("this deletes all data, pass --yes to confirm");
+                }
+                store.reset().await?;
+                println!("{}", "database was reset".colorize("bold green"));
+            }
+        }
+
+        Ok(())
+    }
+}
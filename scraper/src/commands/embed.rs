@@ -0,0 +1,69 @@
+use crate::embeddings::{content_hash, link_text, Embedder};
+use crate::store::{self, LinkEmbedding};
+use anyhow::Result;
+use clap::Args;
+use itertools::Itertools;
+use kdam::{term::Colorizer, tqdm, BarExt};
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct EmbedArgs {
+    #[clap(long, env)]
+    database_url: String,
+
+    /// Directory with `config.json`, `tokenizer.json` and `model.safetensors`
+    #[clap(long, env = "EMBEDDING_MODEL_PATH")]
+    model_path: PathBuf,
+
+    /// Number of links to embed at once
+    #[clap(long, default_value_t = 32, value_parser = clap::value_parser!(u32).range(1..))]
+    batch_size: u32,
+}
+
+impl EmbedArgs {
+    pub async fn run(&self) -> Result<()> {
+        let store = store::connect_migrated(&self.database_url, 1).await?;
+        let embedder = Embedder::load(&self.model_path)?;
+
+        let links = store.embeddable_links(&embedder.name).await?;
+        let total = links.len();
+        let changed = links
+            .into_iter()
+            .map(|l| {
+                let text = link_text(&l.link);
+                let hash = content_hash(&text);
+                (l, text, hash)
+            })
+            .filter(|(l, _, hash)| l.content_hash.as_ref() != Some(hash))
+            .collect_vec();
+
+        let mut pb = tqdm!(total = changed.len());
+        pb.write(
+            format!(
+                "Embedding {} of {} links with {}...",
+                changed.len(),
+                total,
+                embedder.name
+            )
+            .colorize("bold blue"),
+        )?;
+
+        for batch in changed.chunks(self.batch_size as usize) {
+            let texts = batch.iter().map(|(_, text, _)| text.clone()).collect_vec();
+            let embeddings = embedder
+                .embed(&texts)?
+                .into_iter()
+                .zip(batch)
+                .map(|(embedding, (l, _, hash))| LinkEmbedding {
+                    link_id: l.id.clone(),
+                    content_hash: hash.clone(),
+                    embedding,
+                })
+                .collect_vec();
+            store.store_embeddings(&embedder.name, &embeddings).await?;
+            pb.update(batch.len())?;
+        }
+
+        Ok(())
+    }
+}
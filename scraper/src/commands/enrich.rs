@@ -0,0 +1,158 @@
+use crate::awesome_links::github_repo;
+use crate::github::{self, project_view::ProjectDetails};
+use crate::store::{self, ProjectRecord, Store};
+use anyhow::Result;
+use clap::Args;
+use itertools::Itertools;
+use kdam::{term::Colorizer, tqdm, BarExt};
+use std::collections::HashSet;
+use url::Url;
+
+/// How often a repository that moved is followed to its new name before we
+/// give up on it, redirects can go in circles.
+const MAX_PASSES: usize = 4;
+
+#[derive(Debug, Args)]
+pub struct EnrichArgs {
+    #[clap(long, env)]
+    database_url: String,
+
+    #[clap(long, env)]
+    github_token: String,
+
+    /// Number of repositories to fetch per GraphQL request
+    #[clap(long, default_value_t = 50, value_parser = clap::value_parser!(u32).range(1..))]
+    batch_size: u32,
+
+    /// Skip projects that were enriched less than this many hours ago
+    #[clap(long, default_value_t = 24)]
+    refresh_after_hours: i64,
+}
+
+impl EnrichArgs {
+    pub async fn run(&self) -> Result<()> {
+        let store = store::connect_migrated(&self.database_url, 5).await?;
+        let client = github::client(&self.github_token)?;
+
+        let aliases = store.repo_aliases().await?;
+        let fresh = store
+            .enriched_since(chrono::Utc::now() - chrono::Duration::hours(self.refresh_after_hours))
+            .await?;
+        let urls = store.linked_github_urls().await?;
+
+        let mut to_enrich = urls
+            .iter()
+            .filter_map(|url| Url::parse(url).ok())
+            .filter_map(|url| github_repo(&url).map(|(owner, repo)| f!("{owner}/{repo}")))
+            .map(|name| aliases.get(&name.to_lowercase()).cloned().unwrap_or(name))
+            .filter(|name| !fresh.contains(&name.to_lowercase()))
+            .unique_by(|name| name.to_lowercase())
+            .filter_map(|name| {
+                name.split_once('/')
+                    .map(|(owner, repo)| (owner.to_string(), repo.to_string()))
+            })
+            .collect_vec();
+
+        let mut visited = to_enrich
+            .iter()
+            .map(|(owner, repo)| f!("{owner}/{repo}").to_lowercase())
+            .collect::<HashSet<_>>();
+
+        let mut pb = tqdm!(total = to_enrich.len());
+        pb.write("Enriching linked projects...".colorize("bold blue"))?;
+
+        // repositories that moved get another pass under their new name
+        for _ in 0..MAX_PASSES {
+            if to_enrich.is_empty() {
+                break;
+            }
+            let mut moved = Vec::new();
+            for batch in to_enrich.chunks(self.batch_size as usize) {
+                pb.update(batch.len())?;
+                let projects = match github::fetch_projects(&client, batch).await {
+                    Ok(projects) => projects,
+                    Err(e) => {
+                        pb.write(format!("{}: {}", "batch failed".colorize("bold red"), e))?;
+                        continue;
+                    }
+                };
+
+                for ((owner, repo), project) in batch.iter().zip(projects) {
+                    match project {
+                        Some(project) => {
+                            record_alias(&*store, owner, repo, &project.name_with_owner).await?;
+                            store.store_project(&project_record(&project)).await?;
+                        }
+                        None => {
+                            match github::resolve_redirect(&github::GITHUB_URL, owner, repo).await {
+                                Ok(Some((new_owner, new_repo))) => {
+                                    let canonical = f!("{new_owner}/{new_repo}");
+                                    record_alias(&*store, owner, repo, &canonical).await?;
+                                    if visited.insert(canonical.to_lowercase()) {
+                                        moved.push((new_owner, new_repo));
+                                    }
+                                }
+                                Ok(None) => {}
+                                Err(e) => pb.write(format!(
+                                    "{}: {}",
+                                    f!("{owner}/{repo}").colorize("bold red"),
+                                    e
+                                ))?,
+                            }
+                        }
+                    }
+                }
+            }
+            pb.total += moved.len();
+            to_enrich = moved;
+        }
+        if !to_enrich.is_empty() {
+            pb.write(format!(
+                "{}: {}",
+                "gave up following".colorize("bold red"),
+                to_enrich
+                    .iter()
+                    .map(|(owner, repo)| f!("{owner}/{repo}"))
+                    .join(", ")
+            ))?;
+        }
+
+        store.downsample().await?;
+
+        Ok(())
+    }
+}
+
+async fn record_alias(store: &dyn Store, owner: &str, repo: &str, canonical: &str) -> Result<()> {
+    let alias = f!("{owner}/{repo}").to_lowercase();
+    if alias == canonical.to_lowercase() {
+        return Ok(());
+    }
+    store.record_alias(&alias, canonical).await
+}
+
+fn project_record(project: &ProjectDetails) -> ProjectRecord {
+    ProjectRecord {
+        name_with_owner: project.name_with_owner.clone(),
+        url: project.url.clone(),
+        description: project.description.clone(),
+        homepage_url: project.homepage_url.clone(),
+        primary_language: project.primary_language.as_ref().map(|l| l.name.clone()),
+        license: project
+            .license_info
+            .as_ref()
+            .map(|l| l.spdx_id.clone().unwrap_or_else(|| l.name.clone())),
+        topics: project
+            .repository_topics
+            .nodes
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|node| node.topic.name.clone())
+            .collect_vec(),
+        is_archived: project.is_archived,
+        is_fork: project.is_fork,
+        pushed_at: project.pushed_at,
+        metrics: project.into(),
+    }
+}
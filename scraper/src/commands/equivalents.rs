@@ -0,0 +1,62 @@
+use crate::store::{PgStore, Store};
+use anyhow::{bail, Result};
+use clap::Args;
+use kdam::term::Colorizer;
+use search::EquivalentsQuery;
+
+#[derive(Debug, Args)]
+pub struct EquivalentsArgs {
+    /// Project URL, or `owner/repo` for GitHub
+    project: String,
+
+    #[clap(long, env)]
+    database_url: String,
+
+    /// Only equivalents in this language, e.g. `python`
+    #[clap(long)]
+    language: Option<String>,
+
+    #[clap(long, default_value_t = 10)]
+    limit: i64,
+}
+
+impl EquivalentsArgs {
+    pub async fn run(&self) -> Result<()> {
+        if self.database_url.starts_with("sqlite:") {
+            bail!("equivalents need Postgres full-text search, it isn't available for sqlite");
+        }
+        let store = PgStore::connect(&self.database_url, 2).await?;
+        store.ensure_migrated().await?;
+
+        let url = if self.project.contains("://") {
+            self.project.clone()
+        } else {
+            format!("https://github.com/{}", self.project)
+        };
+        let query = EquivalentsQuery {
+            url: url.clone(),
+            language: self.language.clone(),
+            limit: self.limit,
+        };
+
+        let hits = search::equivalents(store.pool(), &query).await?;
+        if hits.is_empty() {
+            println!("no equivalents found, is {url} linked from an ingested list?");
+        }
+        for (i, hit) in hits.iter().enumerate() {
+            println!(
+                "{:>3}. {} {} ({})",
+                i + 1,
+                hit.title.as_str().colorize("bold"),
+                hit.url.as_str().colorize("blue"),
+                hit.language.as_deref().unwrap_or("?")
+            );
+            println!("     {} › {}", hit.list, hit.breadcrumbs.join(" › "));
+            if let Some(description) = &hit.description {
+                println!("     {description}");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,426 @@
+use super::{
+    crawl::CrawledAwesomeList,
+    ingest::{link_url, list_url, read_list},
+};
+use crate::awesome_links::{AwesomeLink, AwesomeLinkType};
+use crate::store;
+use anyhow::Result;
+use arrow_array::{
+    builder::{ListBuilder, StringBuilder},
+    ArrayRef, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use clap::{Args, ValueEnum};
+use itertools::Itertools;
+use kdam::{term::Colorizer, tqdm, BarExt};
+use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Jsonl,
+    Csv,
+    Parquet,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Directory with the crawled `awesome_lists/*.json` files
+    input_path: PathBuf,
+
+    /// Written to stdout if not given
+    #[clap(long, short)]
+    output_path: Option<PathBuf>,
+
+    #[clap(long, value_enum)]
+    format: Format,
+
+    /// Only links added to their list on or after this date, e.g.
+    /// `2023-06-01`. The crawled files have no history, so this reads when
+    /// links were added from the database the lists were ingested into.
+    /// Links a list had when it was first ingested are never included.
+    #[clap(long, requires = "database_url")]
+    since: Option<chrono::NaiveDate>,
+
+    /// Only needed for `--since`
+    #[clap(long, env)]
+    database_url: Option<String>,
+
+    /// Only links of these types
+    #[clap(long = "type", value_enum)]
+    link_types: Vec<AwesomeLinkType>,
+}
+
+impl ExportArgs {
+    pub async fn run(&self) -> Result<()> {
+        let paths = fs::read_dir(&self.input_path)?
+            .flatten()
+            .map(|f| f.path())
+            .filter(|p| p.is_file())
+            .sorted()
+            .collect_vec();
+
+        let output: Box<dyn Write + Send> = match &self.output_path {
+            Some(path) => Box::new(fs::File::create(path)?),
+            None => Box::new(std::io::stdout()),
+        };
+        let mut sink = Sink::new(self.format, BufWriter::new(output))?;
+        let added = self.added_links().await?;
+
+        // one list in memory at a time, the crawl output can be far larger
+        let mut pb = tqdm!(total = paths.len());
+        let mut exported = 0;
+        for path in paths {
+            pb.update(1)?;
+            let list = match read_list(&path) {
+                Ok(list) => list,
+                Err(e) => {
+                    pb.write(format!(
+                        "{}: {:#}",
+                        path.display().to_string().colorize("bold red"),
+                        e
+                    ))?;
+                    continue;
+                }
+            };
+            let added = match &added {
+                Some(added) => match added.get(&list_url(&list)) {
+                    Some(urls) => Some(urls),
+                    None => continue,
+                },
+                None => None,
+            };
+
+            let rows = list
+                .awesome_links
+                .iter()
+                .filter(|l| self.link_types.is_empty() || self.link_types.contains(&l.link_type))
+                .filter(|l| {
+                    added.is_none_or(|added| {
+                        link_url(l.url.as_str()).is_some_and(|url| added.contains(&url))
+                    })
+                })
+                .map(|link| LinkRow { list: &list, link })
+                .collect_vec();
+            sink.write(&rows)?;
+            exported += rows.len();
+        }
+        sink.finish()?;
+
+        pb.write(format!("exported {exported} links"))?;
+        Ok(())
+    }
+
+    /// The urls of the links added since `--since`, by the url of their list.
+    async fn added_links(&self) -> Result<Option<HashMap<String, HashSet<String>>>> {
+        let (Some(since), Some(database_url)) = (self.since, &self.database_url) else {
+            return Ok(None);
+        };
+        let store = store::connect_migrated(database_url, 1).await?;
+        let since = since.and_time(chrono::NaiveTime::MIN).and_utc();
+
+        let mut added: HashMap<String, HashSet<String>> = HashMap::new();
+        for (list_url, url) in store.added_since(since).await? {
+            added.entry(list_url).or_default().insert(url);
+        }
+        Ok(Some(added))
+    }
+}
+
+/// Readers load a whole row group at a time, the default of a million rows
+/// is more than a list export needs to compress well.
+const ROW_GROUP_SIZE: usize = 64 * 1024;
+
+/// A link together with the list it is in, the flat shape every export has.
+pub(super) struct LinkRow<'a> {
+    pub list: &'a CrawledAwesomeList,
+    pub link: &'a AwesomeLink,
+}
+
+#[derive(serde::Serialize)]
+struct JsonRow<'a> {
+    list_owner: &'a str,
+    list_repo: &'a str,
+    list_description: Option<&'a str>,
+    list_stars: Option<i64>,
+    list_topics: &'a [String],
+    crawled_at: chrono::DateTime<chrono::Utc>,
+    latest_commit_at: chrono::DateTime<chrono::Utc>,
+    url: &'a str,
+    title: &'a str,
+    description: Option<&'a str>,
+    breadcrumbs: &'a [String],
+    link_type: &'a AwesomeLinkType,
+    source_line: usize,
+}
+
+impl<'a> From<&LinkRow<'a>> for JsonRow<'a> {
+    fn from(row: &LinkRow<'a>) -> Self {
+        JsonRow {
+            list_owner: &row.list.owner,
+            list_repo: &row.list.repo,
+            list_description: row.list.description.as_deref(),
+            list_stars: row.list.metrics.as_ref().map(|m| m.stars),
+            list_topics: &row.list.topics,
+            crawled_at: row.list.crawled_at,
+            latest_commit_at: row.list.latest_commit_at,
+            url: row.link.url.as_str(),
+            title: &row.link.title,
+            description: row.link.description.as_deref(),
+            breadcrumbs: &row.link.breadcrumbs,
+            link_type: &row.link.link_type,
+            source_line: row.link.source_lines.start,
+        }
+    }
+}
+
+enum Sink<W: Write + Send> {
+    Jsonl(W),
+    Csv(csv::Writer<W>),
+    Parquet(ArrowWriter<W>),
+}
+
+impl<W: Write + Send> Sink<W> {
+    fn new(format: Format, writer: W) -> Result<Self> {
+        Ok(match format {
+            Format::Jsonl => Sink::Jsonl(writer),
+            Format::Csv => {
+                let mut writer = csv::Writer::from_writer(writer);
+                writer.write_record(CSV_HEADER)?;
+                Sink::Csv(writer)
+            }
+            Format::Parquet => Sink::Parquet(ArrowWriter::try_new(
+                writer,
+                Arc::new(links_schema()),
+                Some(
+                    WriterProperties::builder()
+                        .set_max_row_group_size(ROW_GROUP_SIZE)
+                        .build(),
+                ),
+            )?),
+        })
+    }
+
+    fn write(&mut self, rows: &[LinkRow]) -> Result<()> {
+        match self {
+            Sink::Jsonl(writer) => {
+                for row in rows {
+                    serde_json::to_writer(&mut *writer, &JsonRow::from(row))?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+            Sink::Csv(writer) => {
+                for row in rows {
+                    writer.write_record(csv_record(row))?;
+                }
+            }
+            Sink::Parquet(writer) => {
+                if !rows.is_empty() {
+                    writer.write(&links_batch(rows)?)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Sink::Jsonl(mut writer) => writer.flush()?,
+            Sink::Csv(mut writer) => writer.flush()?,
+            Sink::Parquet(writer) => {
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const CSV_HEADER: [&str; 13] = [
+    "list_owner",
+    "list_repo",
+    "list_description",
+    "list_stars",
+    "list_topics",
+    "crawled_at",
+    "latest_commit_at",
+    "url",
+    "title",
+    "description",
+    "breadcrumbs",
+    "link_type",
+    "source_line",
+];
+
+/// CSV has no lists, breadcrumbs are joined like they are displayed and
+/// topics by commas.
+fn csv_record(row: &LinkRow) -> [String; 13] {
+    [
+        row.list.owner.clone(),
+        row.list.repo.clone(),
+        row.list.description.clone().unwrap_or_default(),
+        row.list
+            .metrics
+            .as_ref()
+            .map(|m| m.stars.to_string())
+            .unwrap_or_default(),
+        row.list.topics.join(","),
+        row.list.crawled_at.to_rfc3339(),
+        row.list.latest_commit_at.to_rfc3339(),
+        row.link.url.to_string(),
+        row.link.title.clone(),
+        row.link.description.clone().unwrap_or_default(),
+        row.link.breadcrumbs.join(" > "),
+        format!("{:?}", row.link.link_type),
+        row.link.source_lines.start.to_string(),
+    ]
+}
+
+fn string_list<'a>(values: impl Iterator<Item = &'a [String]>) -> ArrayRef {
+    let mut builder = ListBuilder::new(StringBuilder::new());
+    for value in values {
+        builder.append_value(value.iter().map(Some));
+    }
+    Arc::new(builder.finish())
+}
+
+pub(super) fn links_schema() -> Schema {
+    let timestamp = DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+    let string_list = DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+    Schema::new(vec![
+        Field::new("list_owner", DataType::Utf8, false),
+        Field::new("list_repo", DataType::Utf8, false),
+        Field::new("list_description", DataType::Utf8, true),
+        Field::new("list_stars", DataType::Int64, true),
+        Field::new("list_topics", string_list.clone(), false),
+        Field::new("crawled_at", timestamp.clone(), false),
+        Field::new("latest_commit_at", timestamp, false),
+        Field::new("url", DataType::Utf8, false),
+        Field::new("title", DataType::Utf8, false),
+        Field::new("description", DataType::Utf8, true),
+        Field::new("breadcrumbs", string_list, false),
+        Field::new("link_type", DataType::Utf8, false),
+        Field::new("source_line", DataType::UInt64, false),
+    ])
+}
+
+pub(super) fn links_batch(rows: &[LinkRow]) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| &r.list.owner),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| &r.list.repo),
+        )),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|r| r.list.description.as_deref()),
+        )),
+        Arc::new(Int64Array::from_iter(
+            rows.iter()
+                .map(|r| r.list.metrics.as_ref().map(|m| m.stars)),
+        )),
+        string_list(rows.iter().map(|r| r.list.topics.as_slice())),
+        Arc::new(
+            TimestampMicrosecondArray::from_iter_values(
+                rows.iter().map(|r| r.list.crawled_at.timestamp_micros()),
+            )
+            .with_timezone("UTC"),
+        ),
+        Arc::new(
+            TimestampMicrosecondArray::from_iter_values(
+                rows.iter()
+                    .map(|r| r.list.latest_commit_at.timestamp_micros()),
+            )
+            .with_timezone("UTC"),
+        ),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.link.url.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| &r.link.title),
+        )),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|r| r.link.description.as_deref()),
+        )),
+        string_list(rows.iter().map(|r| r.link.breadcrumbs.as_slice())),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| format!("{:?}", r.link.link_type)),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|r| r.link.source_lines.start as u64),
+        )),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(links_schema()), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn list() -> CrawledAwesomeList {
+        serde_json::from_value(serde_json::json!({
+            "owner": "rust-unofficial",
+            "repo": "awesome-rust",
+            "description": null,
+            "crawled_at": "2023-10-01T00:00:00Z",
+            "latest_commit_at": "2023-09-30T12:00:00Z",
+            "awesome_links": [{
+                "url": "https://github.com/tokio-rs/tokio",
+                "title": "tokio",
+                "breadcrumbs": ["Libraries", "Asynchronous"],
+                "description": "A runtime, for writing \"reliable\" applications",
+                "link_type": "Repo",
+                "source_lines": { "start": 12, "end": 13 },
+            }],
+        }))
+        .unwrap()
+    }
+
+    fn export(format: Format, list: &CrawledAwesomeList) -> Vec<u8> {
+        let rows = list
+            .awesome_links
+            .iter()
+            .map(|link| LinkRow { list, link })
+            .collect_vec();
+        let mut output = vec![];
+        let mut sink = Sink::new(format, &mut output).unwrap();
+        sink.write(&rows).unwrap();
+        sink.write(&rows).unwrap();
+        sink.finish().unwrap();
+        output
+    }
+
+    #[test]
+    fn test_export() {
+        let list = list();
+
+        let jsonl = String::from_utf8(export(Format::Jsonl, &list)).unwrap();
+        let first: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+        assert_eq!(first["list_repo"], "awesome-rust");
+        assert_eq!(first["breadcrumbs"][1], "Asynchronous");
+
+        let csv = export(Format::Csv, &list);
+        let mut reader = csv::Reader::from_reader(csv.as_slice());
+        let records = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(&records[0][10], "Libraries > Asynchronous");
+        assert_eq!(
+            &records[0][9],
+            "A runtime, for writing \"reliable\" applications"
+        );
+
+        let path = std::env::temp_dir().join(f!("epiclist-export-{}.parquet", std::process::id()));
+        fs::write(&path, export(Format::Parquet, &list)).unwrap();
+        let reader = SerializedFileReader::new(fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+}
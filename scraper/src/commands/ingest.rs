@@ -1,11 +1,16 @@
 use super::crawl::CrawledAwesomeList;
-use anyhow::Result;
+use crate::events::LinkState;
+use crate::store::{self, ListRecord, Store};
+use anyhow::{Context, Result};
 use clap::Args;
+use futures::StreamExt;
 use itertools::Itertools;
-use kdam::tqdm;
+use kdam::{term::Colorizer, tqdm, BarExt};
 use normalize_url_rs::normalize_url;
-use sqlx::postgres::PgPoolOptions;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 lazy_static! {
     static ref NORMALIZE_URL_OPTIONS: normalize_url_rs::Options =
@@ -22,122 +27,294 @@ pub struct IngestArgs {
 
     #[clap(long, env)]
     database_url: String,
+
+    /// Number of lists to ingest in parallel
+    #[clap(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    concurrency: u32,
 }
 
 impl IngestArgs {
     pub async fn run(&self) -> Result<()> {
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(&self.database_url)
-            .await?;
+        let store = store::connect_migrated(&self.database_url, self.concurrency).await?;
 
-        let dir_files = fs::read_dir(&self.input_path)?
+        let paths = fs::read_dir(&self.input_path)?
             .flatten()
-            .filter(|f| f.path().is_file());
-
-        for dir_file in tqdm!(dir_files) {
-            let path = dir_file.path();
-            let file = fs::File::open(path).unwrap();
-            let Some(crawled_awesome_list) =
-                serde_json::from_reader::<_, CrawledAwesomeList>(file).ok()
-            else {
-                continue;
-            };
+            .map(|f| f.path())
+            .filter(|p| p.is_file())
+            .collect_vec();
 
-            // then we bulk insert the awesome-links
-            let (urls, data): (Vec<String>, Vec<_>) = crawled_awesome_list
-                .awesome_links
-                .iter()
-                .map(|l| {
-                    let raw_url = &l.url;
-                    let url = normalize_url(raw_url.as_str(), &NORMALIZE_URL_OPTIONS)
-                        .expect("invalid URL");
-
-                    let data = serde_json::json!({
-                        "url": url.clone(),
-                        "title": l.title.clone(),
-                        "description": l.description.clone(),
-                        "breadcrumbs": l.breadcrumbs.to_vec()
-                    });
-                    (url, data)
-                })
-                .sorted_by(|a, b| a.0.cmp(&b.0))
-                .dedup_by(|a, b| a.0 == b.0)
-                .unzip();
-
-            let _links_count = sqlx::query!(
-                r"--sql
-                WITH awesome_list AS (
-                    INSERT INTO awesome_lists (url,
-                        crawled_at,
-                        latest_commit_at)
-                        VALUES($1,
-                            $2,
-                            $3) ON CONFLICT (url)
-                        DO
-                        UPDATE
-                        SET
-                            crawled_at = $2,
-                            latest_commit_at = $3
-                        RETURNING
-                            id
-                ),
-                link_data AS (
-                    SELECT
-                        url,
-                        title,
-                        description,
-                        breadcrumbs
-                    FROM
-                        json_to_recordset($4::json) AS b (url text,
-                            title text,
-                            description text,
-                            breadcrumbs text [])
-                ),
-                awesome_links AS (
-                    INSERT INTO awesome_links (awesome_list_id,
-                        url,
-                        title,
-                        description,
-                        breadcrumbs)
-                SELECT
-                    (SELECT id FROM awesome_list),
-                    url,
-                    title,
-                    description,
-                    breadcrumbs
-                FROM
-                    link_data ON CONFLICT (awesome_list_id,
-                        url)
-                    DO
-                    UPDATE
-                    SET
-                        title = EXCLUDED.title,
-                        description = EXCLUDED.description,
-                        breadcrumbs = EXCLUDED.breadcrumbs
-                    RETURNING
-                        1
-                )
-                SELECT
-                    COUNT(*) AS count
-                FROM
-                    awesome_links;
-            ",
-                f!("https://github.com/{crawled_awesome_list.owner}/{crawled_awesome_list.repo}"),
-                crawled_awesome_list.crawled_at,
-                crawled_awesome_list.latest_commit_at,
-                serde_json::Value::Array(data)
-            )
-            .fetch_one(&pool)
-            .await?
-            .count
-            .unwrap_or_default();
-
-            //todo: expire old links & projects
-            //which might be just as easy as truncating the database everytime and just ensuring
-            //that any "saves" to epic-lists are not dependent on it
+        let mut pb = tqdm!(total = paths.len());
+        let mut summary = IngestSummary::default();
+
+        let mut results = futures::stream::iter(paths)
+            .map(|path| {
+                let store = &*store;
+                async move {
+                    let result = match read_list(&path) {
+                        Ok(list) => ingest_list(store, list).await,
+                        Err(e) => Err(e),
+                    };
+                    (path, result)
+                }
+            })
+            .buffer_unordered(self.concurrency as usize);
+
+        while let Some((path, result)) = results.next().await {
+            pb.update(1)?;
+            match result {
+                Ok(list_summary) => {
+                    for bad_url in &list_summary.bad_urls {
+                        pb.write(format!(
+                            "{}: skipped invalid url {}",
+                            path.display().to_string().colorize("yellow"),
+                            bad_url
+                        ))?;
+                    }
+                    summary.add(list_summary);
+                }
+                Err(e) => {
+                    summary.skipped_files += 1;
+                    pb.write(format!(
+                        "{}: {:#}",
+                        path.display().to_string().colorize("bold red"),
+                        e
+                    ))?;
+                }
+            }
         }
+        drop(results);
+
+        store.downsample().await?;
+
+        pb.write(format!(
+            "{} lists ingested, {} files skipped\nlinks: {} inserted, {} updated, {} removed, {} skipped",
+            summary.lists,
+            summary.skipped_files,
+            summary.inserted,
+            summary.updated,
+            summary.removed,
+            summary.skipped_links
+        ))?;
 
         Ok(())
     }
 }
+
+#[derive(Debug, Default)]
+struct IngestSummary {
+    lists: u64,
+    skipped_files: u64,
+    inserted: u64,
+    updated: u64,
+    removed: u64,
+    skipped_links: u64,
+}
+
+#[derive(Debug, Default)]
+struct ListSummary {
+    inserted: u64,
+    updated: u64,
+    removed: u64,
+    bad_urls: Vec<String>,
+}
+
+impl IngestSummary {
+    fn add(&mut self, list: ListSummary) {
+        self.lists += 1;
+        self.inserted += list.inserted;
+        self.updated += list.updated;
+        self.removed += list.removed;
+        self.skipped_links += list.bad_urls.len() as u64;
+    }
+}
+
+pub(super) fn read_list(path: &Path) -> Result<CrawledAwesomeList> {
+    let file = fs::File::open(path)?;
+    let reader = std::io::BufReader::new(file);
+    serde_json::from_reader(reader).context("not a crawled awesome list")
+}
+
+/// Loads one list into the database inside a single transaction, so a list is
+/// either fully updated or not at all.
+async fn ingest_list(store: &dyn Store, list: CrawledAwesomeList) -> Result<ListSummary> {
+    let (record, links, bad_urls) = list_record(&list);
+    let ingested = store.ingest_list(&record, &links).await?;
+
+    Ok(ListSummary {
+        inserted: ingested.inserted,
+        updated: ingested.updated,
+        removed: ingested.removed,
+        bad_urls,
+    })
+}
+
+/// The url a crawled list is stored under.
+pub(super) fn list_url(list: &CrawledAwesomeList) -> String {
+    f!("https://github.com/{list.owner}/{list.repo}")
+}
+
+/// The url a link is stored under, `None` if it can't be normalized.
+pub(super) fn link_url(url: &str) -> Option<String> {
+    normalize_url(url, &NORMALIZE_URL_OPTIONS).ok()
+}
+
+/// Splits a crawled list into what we store, normalizing and deduplicating
+/// its links. Links whose url can't be normalized are returned separately.
+fn list_record(list: &CrawledAwesomeList) -> (ListRecord, Vec<LinkState>, Vec<String>) {
+    let mut bad_urls = Vec::new();
+    let links = list
+        .awesome_links
+        .iter()
+        .filter_map(|l| {
+            let raw_url = &l.url;
+            let Some(url) = link_url(raw_url.as_str()) else {
+                bad_urls.push(raw_url.to_string());
+                return None;
+            };
+
+            Some(LinkState {
+                url,
+                title: l.title.clone(),
+                description: l.description.clone(),
+                breadcrumbs: l.breadcrumbs.to_vec(),
+                link_type: Some(format!("{:?}", l.link_type)),
+                source_line: Some(l.source_lines.start as i32),
+            })
+        })
+        .sorted_by(|a, b| a.url.cmp(&b.url))
+        .dedup_by(|a, b| a.url == b.url)
+        .collect_vec();
+
+    let record = ListRecord {
+        url: list_url(list),
+        owner: list.owner.clone(),
+        repo: list.repo.clone(),
+        description: list.description.clone(),
+        crawled_at: list.crawled_at,
+        latest_commit_at: list.latest_commit_at,
+        homepage_url: list.homepage_url.clone(),
+        topics: list.topics.clone(),
+        default_branch: list.default_branch.clone(),
+        readme_path: list.readme_path.clone(),
+        parent_url: list
+            .parent
+            .as_ref()
+            .map(|parent| f!("https://github.com/{parent}")),
+        metrics: list.metrics.clone(),
+    };
+
+    (record, links, bad_urls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::SqliteStore;
+
+    fn crawled(crawled_at: &str, stars: i64, links: &[(&str, &str)]) -> CrawledAwesomeList {
+        let links = links
+            .iter()
+            .map(|(url, section)| {
+                serde_json::json!({
+                    "url": url,
+                    "title": url.rsplit('/').next().unwrap(),
+                    "breadcrumbs": [section],
+                    "description": null,
+                    "link_type": "Repo",
+                    "source_lines": { "start": 0, "end": 1 },
+                })
+            })
+            .collect_vec();
+
+        serde_json::from_value(serde_json::json!({
+            "owner": "rust-unofficial",
+            "repo": "awesome-rust",
+            "description": "A curated list of Rust code and resources.",
+            "crawled_at": crawled_at,
+            "latest_commit_at": crawled_at,
+            "metrics": { "stars": stars, "forks": 10, "open_issues": 2, "commits": null },
+            "awesome_links": links,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ingest_sqlite() {
+        let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+        store.migrate().await.unwrap();
+        store.ensure_migrated().await.unwrap();
+
+        let first = crawled(
+            "2023-10-01T00:00:00Z",
+            100,
+            &[
+                ("https://github.com/tokio-rs/tokio", "Async"),
+                ("https://github.com/serde-rs/serde", "Encoding"),
+                ("http://github.com/serde-rs/serde/", "Encoding"),
+            ],
+        );
+        let summary = ingest_list(&store, first).await.unwrap();
+        assert_eq!(summary.inserted, 2);
+        assert!(summary.bad_urls.is_empty());
+
+        let second = crawled(
+            "2023-10-08T00:00:00Z",
+            150,
+            &[
+                ("https://github.com/tokio-rs/tokio", "Runtimes"),
+                ("https://github.com/rayon-rs/rayon", "Concurrency"),
+            ],
+        );
+        let summary = ingest_list(&store, second).await.unwrap();
+        assert_eq!(
+            (summary.inserted, summary.updated, summary.removed),
+            (1, 1, 1)
+        );
+
+        // nothing changed, so nothing is counted
+        let again = crawled(
+            "2023-10-09T00:00:00Z",
+            150,
+            &[
+                ("https://github.com/tokio-rs/tokio", "Runtimes"),
+                ("https://github.com/rayon-rs/rayon", "Concurrency"),
+            ],
+        );
+        let summary = ingest_list(&store, again).await.unwrap();
+        assert_eq!(
+            (summary.inserted, summary.updated, summary.removed),
+            (0, 0, 0)
+        );
+
+        let mut urls = store.linked_github_urls().await.unwrap();
+        urls.sort();
+        assert_eq!(
+            urls,
+            vec![
+                "https://github.com/rayon-rs/rayon",
+                "https://github.com/serde-rs/serde",
+                "https://github.com/tokio-rs/tokio",
+            ]
+        );
+
+        // export --since looks crawled links up by these urls
+        let added = store
+            .added_since("2023-10-01T00:00:00Z".parse().unwrap())
+            .await
+            .unwrap();
+        let list = list_url(&crawled("2023-10-09T00:00:00Z", 150, &[]));
+        let rayon = link_url("http://github.com/rayon-rs/rayon/").unwrap();
+        assert_eq!(added, std::collections::HashSet::from([(list, rayon)]));
+
+        let growth = store
+            .growth(
+                "rust-unofficial/awesome-rust",
+                chrono::Duration::days(36500),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(growth.stars, 50);
+        assert_eq!(growth.commits, None);
+    }
+}
@@ -4,11 +4,157 @@ use std::path::PathBuf;
 
 #[derive(Debug, Args)]
 pub struct LanceArgs {
+    /// Directory with the crawled `awesome_lists/*.json` files
+    input_path: PathBuf,
+
     output_path: PathBuf,
 }
 
+/// Lance pulls in datafusion, so the command is always there but only
+/// does something when built with the `lance` feature, see `just lance`.
+#[cfg(not(feature = "lance"))]
 impl LanceArgs {
     pub async fn run(&self) -> Result<()> {
-        todo!()
+        anyhow::bail!(
+            "can't write {} to {}, this scraper was built without the `lance` feature",
+            self.input_path.display(),
+            self.output_path.display()
+        )
+    }
+}
+
+#[cfg(feature = "lance")]
+mod dataset {
+    use super::LanceArgs;
+    use crate::commands::{
+        crawl::CrawledAwesomeList,
+        export::{links_batch, LinkRow},
+        ingest::read_list,
+    };
+    use anyhow::{Context, Result};
+    use arrow_array::RecordBatchIterator;
+    use itertools::Itertools;
+    use kdam::{term::Colorizer, tqdm, BarExt};
+    use lance::{
+        dataset::{WriteMode, WriteParams},
+        Dataset,
+    };
+    use std::fs;
+
+    impl LanceArgs {
+        pub async fn run(&self) -> Result<()> {
+            let paths = fs::read_dir(&self.input_path)?
+                .flatten()
+                .map(|f| f.path())
+                .filter(|p| p.is_file())
+                .sorted()
+                .collect_vec();
+
+            let mut pb = tqdm!(total = paths.len());
+            let mut lists = Vec::with_capacity(paths.len());
+            for path in paths {
+                pb.update(1)?;
+                match read_list(&path) {
+                    Ok(list) => lists.push(list),
+                    Err(e) => pb.write(format!(
+                        "{}: {:#}",
+                        path.display().to_string().colorize("bold red"),
+                        e
+                    ))?,
+                }
+            }
+
+            let uri = self
+                .output_path
+                .to_str()
+                .context("output path is not valid utf-8")?;
+            let dataset = write_links(&lists, uri).await?;
+
+            pb.write(format!(
+                "wrote {} links from {} lists as version {}",
+                dataset.count_rows(None).await?,
+                lists.len(),
+                dataset.version().version
+            ))?;
+
+            Ok(())
+        }
+    }
+
+    /// Writes the links as a new version of the dataset, older runs stay
+    /// available through lance's time travel.
+    async fn write_links(lists: &[CrawledAwesomeList], uri: &str) -> Result<Dataset> {
+        let rows = lists
+            .iter()
+            .flat_map(|list| {
+                list.awesome_links
+                    .iter()
+                    .map(move |link| LinkRow { list, link })
+            })
+            .collect_vec();
+        let batch = links_batch(&rows)?;
+        let schema = batch.schema();
+        let params = WriteParams {
+            mode: WriteMode::Overwrite,
+            ..Default::default()
+        };
+
+        Ok(Dataset::write(
+            RecordBatchIterator::new([Ok(batch)], schema),
+            uri,
+            Some(params),
+        )
+        .await?)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_write_links() {
+            let list: CrawledAwesomeList = serde_json::from_value(serde_json::json!({
+                "owner": "rust-unofficial",
+                "repo": "awesome-rust",
+                "description": null,
+                "crawled_at": "2023-10-01T00:00:00Z",
+                "latest_commit_at": "2023-09-30T12:00:00Z",
+                "awesome_links": [{
+                    "url": "https://github.com/tokio-rs/tokio",
+                    "title": "tokio",
+                    "breadcrumbs": ["Libraries", "Asynchronous"],
+                    "description": "A runtime for writing reliable asynchronous applications",
+                    "link_type": "Repo",
+                    "source_lines": { "start": 12, "end": 13 },
+                }],
+            }))
+            .unwrap();
+
+            let path = std::env::temp_dir().join(f!("epiclist-lance-{}", std::process::id()));
+            let uri = path.to_str().unwrap();
+            let _ = fs::remove_dir_all(&path);
+
+            write_links(std::slice::from_ref(&list), uri).await.unwrap();
+            let dataset = write_links(&[list], uri).await.unwrap();
+            assert_eq!(dataset.version().version, 2);
+            assert_eq!(dataset.count_rows(None).await.unwrap(), 1);
+
+            fs::remove_dir_all(&path).unwrap();
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "lance")))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_without_feature() {
+        let args = LanceArgs {
+            input_path: "data".into(),
+            output_path: "links.lance".into(),
+        };
+        let err = args.run().await.unwrap_err();
+        assert!(err.to_string().contains("built without the `lance` feature"));
     }
 }
@@ -2,17 +2,45 @@ use clap::Subcommand;
 use kdam::{term, term::Colorizer, tqdm, BarExt, Column, RichProgress, Spinner};
 
 mod crawl;
+mod db;
+mod embed;
+mod enrich;
+mod equivalents;
+mod export;
 mod ingest;
 mod lance;
+mod search;
+mod snapshots;
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
     /// Start a broad crawl of awesome lists
     Crawl(crawl::CrawlArgs),
 
+    /// Manage the database schema
+    Db(db::DbArgs),
+
     /// Load crawled data into a database
     Ingest(ingest::IngestArgs),
 
-    // Convert crawled data to a lance dataset
+    /// Fetch GitHub metrics for the projects linked from ingested lists
+    Enrich(enrich::EnrichArgs),
+
+    /// Compute embeddings for links whose text changed since the last run
+    Embed(embed::EmbedArgs),
+
+    /// Find links by text and, with a model, by meaning
+    Search(search::SearchArgs),
+
+    /// Find projects doing the same thing in other languages
+    Equivalents(equivalents::EquivalentsArgs),
+
+    /// Inspect and prune historical repository metrics
+    Snapshots(snapshots::SnapshotsArgs),
+
+    /// Write crawled links as one flat row per link
+    Export(export::ExportArgs),
+
+    /// Convert crawled data to a lance dataset, needs the `lance` feature
     Lance(lance::LanceArgs),
 }
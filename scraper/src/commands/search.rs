@@ -0,0 +1,91 @@
+use crate::embeddings::Embedder;
+use crate::store::{PgStore, Store};
+use anyhow::{bail, Result};
+use clap::Args;
+use kdam::term::Colorizer;
+use search::{QueryEmbedding, SearchQuery};
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Words to look for, supports `"quoted phrases"`, `or` and `-negation`
+    query: String,
+
+    #[clap(long, env)]
+    database_url: String,
+
+    /// Also rank by similarity to links embedded with this model, see `embed`
+    #[clap(long, env = "EMBEDDING_MODEL_PATH")]
+    model_path: Option<PathBuf>,
+
+    /// Only links of this type, e.g. `Repo` or `Article`
+    #[clap(long = "type")]
+    link_type: Option<String>,
+
+    /// Only links from this list, as `owner/repo`
+    #[clap(long)]
+    list: Option<String>,
+
+    /// Only projects written in this language, or links from `awesome-{language}`
+    #[clap(long)]
+    language: Option<String>,
+
+    /// Only projects pushed to on or after this date, e.g. `2023-06-01`
+    #[clap(long)]
+    pushed_since: Option<chrono::NaiveDate>,
+
+    #[clap(long, default_value_t = 20)]
+    limit: i64,
+}
+
+impl SearchArgs {
+    pub async fn run(&self) -> Result<()> {
+        if self.database_url.starts_with("sqlite:") {
+            bail!("search needs Postgres full-text search, it isn't available for sqlite");
+        }
+        let store = PgStore::connect(&self.database_url, 2).await?;
+        store.ensure_migrated().await?;
+
+        let embedding = match &self.model_path {
+            Some(model_path) => {
+                let embedder = Embedder::load(model_path)?;
+                let vector = embedder.embed(std::slice::from_ref(&self.query))?.remove(0);
+                Some(QueryEmbedding {
+                    model: embedder.name,
+                    vector,
+                })
+            }
+            None => None,
+        };
+
+        let query = SearchQuery {
+            text: self.query.clone(),
+            link_type: self.link_type.clone(),
+            list: self.list.clone(),
+            language: self.language.clone(),
+            pushed_since: self
+                .pushed_since
+                .map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc()),
+            limit: self.limit,
+        };
+
+        let hits = search::search(store.pool(), &query, embedding.as_ref()).await?;
+        if hits.is_empty() {
+            println!("no links found");
+        }
+        for (i, hit) in hits.iter().enumerate() {
+            println!(
+                "{:>3}. {} {}",
+                i + 1,
+                hit.title.as_str().colorize("bold"),
+                hit.url.as_str().colorize("blue")
+            );
+            println!("     {} › {}", hit.list, hit.breadcrumbs.join(" › "));
+            if let Some(description) = &hit.description {
+                println!("     {description}");
+            }
+        }
+
+        Ok(())
+    }
+}
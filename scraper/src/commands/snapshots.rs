@@ -0,0 +1,62 @@
+use crate::store;
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Args)]
+pub struct SnapshotsArgs {
+    #[clap(long, env)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: SnapshotsCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum SnapshotsCommand {
+    /// Show how a repository's metrics changed over a window
+    Growth {
+        /// Repository as `owner/repo`
+        name_with_owner: String,
+
+        #[clap(long, default_value_t = 30)]
+        days: i64,
+    },
+
+    /// Thin out old snapshots according to the retention rules
+    Prune,
+}
+
+impl SnapshotsArgs {
+    pub async fn run(&self) -> Result<()> {
+        let store = store::connect_migrated(&self.database_url, 1).await?;
+
+        match &self.command {
+            SnapshotsCommand::Growth {
+                name_with_owner,
+                days,
+            } => match store.growth(name_with_owner, chrono::Duration::days(*days))
+                .await?
+            {
+                Some(growth) => println!(
+                    "{name_with_owner} ({} → {}): {:+} stars, {:+} forks, {:+} open issues, {} commits",
+                    growth.from.date_naive(),
+                    growth.to.date_naive(),
+                    growth.stars,
+                    growth.forks,
+                    growth.open_issues,
+                    growth
+                        .commits
+                        .map(|c| format!("{c:+}"))
+                        .unwrap_or_else(|| String::from("?"))
+                ),
+                None => println!("{name_with_owner}: not enough snapshots"),
+            },
+            SnapshotsCommand::Prune => {
+                let removed = store.downsample().await?;
+                println!("removed {removed} snapshots");
+            }
+        }
+
+        Ok(())
+    }
+}
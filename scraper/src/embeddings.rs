@@ -0,0 +1,207 @@
+use crate::events::LinkState;
+use anyhow::{Context, Result};
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+/// Longer texts are cut off, link descriptions rarely come close.
+const MAX_TOKENS: usize = 256;
+
+/// The text we embed for a link, so a search for "http client" also
+/// matches links that only mention it in their section.
+pub fn link_text(link: &LinkState) -> String {
+    let mut text = link.breadcrumbs.join(" > ");
+    if !text.is_empty() {
+        text.push_str(": ");
+    }
+    text.push_str(&link.title);
+    if let Some(description) = &link.description {
+        text.push_str(" - ");
+        text.push_str(description);
+    }
+    text
+}
+
+/// Identifies the text an embedding was computed from, so unchanged links
+/// don't get embedded again.
+pub fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// A sentence-transformers style BERT model running on the CPU.
+pub struct Embedder {
+    /// Stored with every embedding, switching models re-embeds everything
+    pub name: String,
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+}
+
+impl Embedder {
+    /// Loads `config.json`, `tokenizer.json` and `model.safetensors` from a
+    /// model directory, e.g. a clone of `sentence-transformers/all-MiniLM-L6-v2`.
+    pub fn load(model_path: &Path) -> Result<Self> {
+        let device = Device::Cpu;
+        let name = model_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .context("model path has no directory name")?
+            .to_string();
+
+        let config: Config =
+            serde_json::from_reader(std::fs::File::open(model_path.join("config.json"))?)
+                .context("invalid config.json")?;
+
+        let mut tokenizer =
+            Tokenizer::from_file(model_path.join("tokenizer.json")).map_err(anyhow::Error::msg)?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS,
+                ..Default::default()
+            }))
+            .map_err(anyhow::Error::msg)?;
+
+        let vb = unsafe {
+            VarBuilder::from_mmaped_safetensors(
+                &[model_path.join("model.safetensors")],
+                DType::F32,
+                &device,
+            )?
+        };
+        let model = BertModel::load(vb, &config)?;
+
+        Ok(Self {
+            name,
+            model,
+            tokenizer,
+            device,
+        })
+    }
+
+    /// Mean-pooled, normalized sentence embeddings, one per text.
+    pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(anyhow::Error::msg)?;
+
+        let ids = encodings
+            .iter()
+            .map(|e| Tensor::new(e.get_ids(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let mask = encodings
+            .iter()
+            .map(|e| Tensor::new(e.get_attention_mask(), &self.device))
+            .collect::<candle_core::Result<Vec<_>>>()?;
+        let ids = Tensor::stack(&ids, 0)?;
+        let mask = Tensor::stack(&mask, 0)?;
+
+        let hidden = self.model.forward(&ids, &ids.zeros_like()?, Some(&mask))?;
+
+        // average over the real tokens only, padding would otherwise pull
+        // short texts towards each other
+        let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+        let pooled = hidden
+            .broadcast_mul(&mask)?
+            .sum(1)?
+            .broadcast_div(&mask.sum(1)?)?;
+        let normalized = pooled.broadcast_div(&pooled.sqr()?.sum_keepdim(1)?.sqrt()?)?;
+
+        Ok(normalized.to_vec2()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_nn::VarMap;
+    use std::collections::HashMap;
+    use tokenizers::{models::wordlevel::WordLevel, pre_tokenizers::whitespace::Whitespace};
+
+    /// Writes a tiny randomly initialized model, real ones are too big to
+    /// check in.
+    fn tiny_model(dir: &Path) {
+        let config = serde_json::json!({
+            "vocab_size": 8,
+            "hidden_size": 16,
+            "num_hidden_layers": 1,
+            "num_attention_heads": 2,
+            "intermediate_size": 32,
+            "hidden_act": "gelu",
+            "hidden_dropout_prob": 0.0,
+            "max_position_embeddings": 64,
+            "type_vocab_size": 2,
+            "initializer_range": 0.02,
+            "layer_norm_eps": 1e-12,
+            "pad_token_id": 0,
+            "classifier_dropout": null,
+            "model_type": "bert"
+        });
+        std::fs::write(dir.join("config.json"), config.to_string()).unwrap();
+
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+        BertModel::load(vb, &serde_json::from_value(config).unwrap()).unwrap();
+        varmap.save(dir.join("model.safetensors")).unwrap();
+
+        let vocab: HashMap<String, u32> = ["[PAD]", "[UNK]", "http", "client", "async", "runtime"]
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.to_string(), i as u32))
+            .collect();
+        let mut tokenizer = Tokenizer::new(
+            WordLevel::builder()
+                .vocab(vocab.into_iter().collect())
+                .unk_token("[UNK]".into())
+                .build()
+                .unwrap(),
+        );
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer.save(dir.join("tokenizer.json"), false).unwrap();
+    }
+
+    #[test]
+    fn test_embed() {
+        let dir = std::env::temp_dir().join(f!("epiclist-embedder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        tiny_model(&dir);
+
+        let embedder = Embedder::load(&dir).unwrap();
+        let alone = embedder.embed(&["http client".into()]).unwrap();
+        let batch = embedder
+            .embed(&["async runtime async runtime".into(), "http client".into()])
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[1].len(), 16);
+        let norm = batch[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        // padding to the longer text in the batch must not change the result
+        for (a, b) in alone[0].iter().zip(&batch[1]) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_link_text() {
+        let link = LinkState {
+            url: "https://github.com/seanmonstar/reqwest".into(),
+            title: "reqwest".into(),
+            description: Some("An easy and powerful HTTP client".into()),
+            breadcrumbs: vec!["Libraries".into(), "Web programming".into()],
+            link_type: Some("Repo".into()),
+            source_line: None,
+        };
+        assert_eq!(
+            link_text(&link),
+            "Libraries > Web programming: reqwest - An easy and powerful HTTP client"
+        );
+        assert_eq!(content_hash("a"), content_hash("a"));
+        assert_ne!(content_hash("a"), content_hash("b"));
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+/// The parts of a link we store and track changes to.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LinkState {
+    pub url: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub breadcrumbs: Vec<String>,
+    pub link_type: Option<String>,
+    /// The line of the README the link starts on, only kept to show links in
+    /// README order. `None` for links stored before it was.
+    pub source_line: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkEventKind {
+    Added,
+    Removed,
+    Moved,
+    DescriptionChanged,
+}
+
+impl LinkEventKind {
+    /// The name stored in the `kind` column, same as the serde name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkEventKind::Added => "added",
+            LinkEventKind::Removed => "removed",
+            LinkEventKind::Moved => "moved",
+            LinkEventKind::DescriptionChanged => "description_changed",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct LinkEvent {
+    pub url: String,
+    pub kind: LinkEventKind,
+    pub old_breadcrumbs: Option<Vec<String>>,
+    pub new_breadcrumbs: Option<Vec<String>>,
+    pub old_description: Option<String>,
+    pub new_description: Option<String>,
+}
+
+/// Compares the links currently stored for a list with a fresh crawl of it.
+/// `previous` is `None` on the first crawl of a list, its links were there
+/// all along rather than just added.
+pub fn diff_links(previous: Option<&[LinkState]>, current: &[LinkState]) -> Vec<LinkEvent> {
+    let Some(previous) = previous else {
+        return Vec::new();
+    };
+    let previous_by_url: HashMap<_, _> = previous.iter().map(|l| (l.url.as_str(), l)).collect();
+    let current_by_url: HashMap<_, _> = current.iter().map(|l| (l.url.as_str(), l)).collect();
+
+    let mut events = Vec::new();
+    for link in current {
+        let Some(old) = previous_by_url.get(link.url.as_str()) else {
+            events.push(LinkEvent {
+                url: link.url.clone(),
+                kind: LinkEventKind::Added,
+                old_breadcrumbs: None,
+                new_breadcrumbs: Some(link.breadcrumbs.clone()),
+                old_description: None,
+                new_description: link.description.clone(),
+            });
+            continue;
+        };
+
+        if old.breadcrumbs != link.breadcrumbs {
+            events.push(LinkEvent {
+                url: link.url.clone(),
+                kind: LinkEventKind::Moved,
+                old_breadcrumbs: Some(old.breadcrumbs.clone()),
+                new_breadcrumbs: Some(link.breadcrumbs.clone()),
+                old_description: None,
+                new_description: None,
+            });
+        }
+        if old.description != link.description {
+            events.push(LinkEvent {
+                url: link.url.clone(),
+                kind: LinkEventKind::DescriptionChanged,
+                old_breadcrumbs: None,
+                new_breadcrumbs: None,
+                old_description: old.description.clone(),
+                new_description: link.description.clone(),
+            });
+        }
+    }
+
+    for link in previous {
+        if !current_by_url.contains_key(link.url.as_str()) {
+            events.push(LinkEvent {
+                url: link.url.clone(),
+                kind: LinkEventKind::Removed,
+                old_breadcrumbs: Some(link.breadcrumbs.clone()),
+                new_breadcrumbs: None,
+                old_description: link.description.clone(),
+                new_description: None,
+            });
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str, description: &str, breadcrumbs: &[&str]) -> LinkState {
+        LinkState {
+            url: url.to_string(),
+            title: url.to_string(),
+            description: Some(description.to_string()),
+            breadcrumbs: breadcrumbs.iter().map(|b| b.to_string()).collect(),
+            link_type: None,
+            source_line: None,
+        }
+    }
+
+    fn kinds(events: Vec<LinkEvent>) -> Vec<(String, LinkEventKind)> {
+        events.into_iter().map(|e| (e.url, e.kind)).collect()
+    }
+
+    #[test]
+    fn test_diff_links_added() {
+        let previous = vec![link("https://a", "A thing", &["Web"])];
+        let current = vec![
+            link("https://a", "A thing", &["Web"]),
+            link("https://d", "D thing", &["CLI"]),
+        ];
+
+        let events = diff_links(Some(&previous), &current);
+        assert_eq!(
+            kinds(events),
+            vec![(String::from("https://d"), LinkEventKind::Added)]
+        );
+    }
+
+    #[test]
+    fn test_diff_links_first_crawl() {
+        let current = vec![link("https://a", "A thing", &["Web"])];
+
+        assert!(diff_links(None, &current).is_empty());
+        // a list that was crawled before but had no links does get additions
+        assert_eq!(diff_links(Some(&[]), &current).len(), 1);
+    }
+
+    #[test]
+    fn test_diff_links_moved() {
+        let previous = vec![link("https://b", "B thing", &["Web"])];
+        let current = vec![link("https://b", "B thing", &["Web", "Frameworks"])];
+
+        let events = diff_links(Some(&previous), &current);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, LinkEventKind::Moved);
+        assert_eq!(events[0].old_breadcrumbs, Some(vec![String::from("Web")]));
+        assert_eq!(
+            events[0].new_breadcrumbs,
+            Some(vec![String::from("Web"), String::from("Frameworks")])
+        );
+    }
+
+    #[test]
+    fn test_diff_links_description_changed() {
+        let previous = vec![link("https://c", "C thing", &["Web"])];
+        let current = vec![link("https://c", "The C thing", &["Web"])];
+
+        let events = diff_links(Some(&previous), &current);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, LinkEventKind::DescriptionChanged);
+        assert_eq!(events[0].old_description.as_deref(), Some("C thing"));
+        assert_eq!(events[0].new_description.as_deref(), Some("The C thing"));
+    }
+
+    #[test]
+    fn test_diff_links_moved_and_described() {
+        let previous = vec![link("https://c", "C thing", &["Web"])];
+        let current = vec![link("https://c", "The C thing", &["CLI"])];
+
+        assert_eq!(
+            kinds(diff_links(Some(&previous), &current)),
+            vec![
+                (String::from("https://c"), LinkEventKind::Moved),
+                (String::from("https://c"), LinkEventKind::DescriptionChanged),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_links_removed() {
+        let previous = vec![
+            link("https://a", "A thing", &["Web"]),
+            link("https://b", "B thing", &["Web"]),
+        ];
+        let current = vec![link("https://a", "A thing", &["Web"])];
+
+        let events = diff_links(Some(&previous), &current);
+        assert_eq!(
+            kinds(events),
+            vec![(String::from("https://b"), LinkEventKind::Removed)]
+        );
+    }
+
+    #[test]
+    fn test_diff_links_unchanged() {
+        let links = vec![
+            link("https://a", "A thing", &["Web"]),
+            link("https://b", "B thing", &["CLI"]),
+        ];
+
+        assert!(diff_links(Some(&links), &links).is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use super::{DateTime, GRAPHQL_URL, URI};
+use anyhow::{Context, Result};
+use graphql_client::GraphQLQuery;
+use itertools::Itertools;
+use reqwest::Client;
+use std::collections::HashMap;
+
+// we only post the batched document ourselves, this is here for the generated types
+#[allow(dead_code)]
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "../schemas/github.graphql",
+    query_path = "src/github/_project_view.graphql",
+    response_derives = "Debug,Serialize,Deserialize,Clone"
+)]
+pub struct ProjectView;
+
+const PROJECT_VIEW_DOCUMENT: &str = include_str!("_project_view.graphql");
+
+/// Fetches a whole batch of projects in one request by aliasing a
+/// `repository` field per project. Anything GitHub couldn't find comes back
+/// as `None`, in the same order as `repos`.
+pub async fn fetch_projects(
+    client: &Client,
+    repos: &[(String, String)],
+) -> Result<Vec<Option<project_view::ProjectDetails>>> {
+    let res = client
+        .post(GRAPHQL_URL)
+        .json(&batch_request(repos))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    batch_results(res, repos.len())
+}
+
+type BatchResponse =
+    graphql_client::Response<HashMap<String, Option<project_view::ProjectDetails>>>;
+
+/// Picks the projects back out of the aliased fields of `batch_request`.
+fn batch_results(
+    res: BatchResponse,
+    len: usize,
+) -> Result<Vec<Option<project_view::ProjectDetails>>> {
+    // missing repositories show up as NOT_FOUND errors next to partial data
+    let mut data = res.data.context("missing project response data")?;
+    Ok((0..len)
+        .map(|i| data.remove(&format!("r{i}")).flatten())
+        .collect())
+}
+
+/// The request body querying `repos` as `r0`, `r1`, ...
+fn batch_request(repos: &[(String, String)]) -> serde_json::Value {
+    let fragment = &PROJECT_VIEW_DOCUMENT[..PROJECT_VIEW_DOCUMENT
+        .find("query ProjectView")
+        .unwrap_or(PROJECT_VIEW_DOCUMENT.len())];
+    let params = (0..repos.len())
+        .map(|i| format!("$owner{i}: String!, $name{i}: String!"))
+        .join(", ");
+    let fields = (0..repos.len())
+        .map(|i| {
+            format!("  r{i}: repository(owner: $owner{i}, name: $name{i}) {{ ...ProjectDetails }}")
+        })
+        .join("\n");
+    let query = format!("query ProjectViewBatch({params}) {{\n{fields}\n}}\n\n{fragment}");
+
+    let variables = repos
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (owner, name))| {
+            [
+                (format!("owner{i}"), serde_json::Value::from(owner.as_str())),
+                (format!("name{i}"), serde_json::Value::from(name.as_str())),
+            ]
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    serde_json::json!({ "query": query, "variables": variables })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_request() {
+        let repos = [
+            (String::from("tokio-rs"), String::from("tokio")),
+            (String::from("serde-rs"), String::from("serde")),
+        ];
+        let request = batch_request(&repos);
+        let query = request["query"].as_str().unwrap();
+
+        assert!(query.starts_with(
+            "query ProjectViewBatch($owner0: String!, $name0: String!, $owner1: String!, $name1: String!) {\n"
+        ));
+        assert!(query
+            .contains("  r0: repository(owner: $owner0, name: $name0) { ...ProjectDetails }\n"));
+        assert!(query
+            .contains("  r1: repository(owner: $owner1, name: $name1) { ...ProjectDetails }\n"));
+        // the fragment comes along, the single repository query doesn't
+        assert_eq!(
+            query
+                .matches("fragment ProjectDetails on Repository")
+                .count(),
+            1
+        );
+        assert!(!query.contains("query ProjectView("));
+        assert_eq!(
+            request["variables"],
+            serde_json::json!({
+                "owner0": "tokio-rs",
+                "name0": "tokio",
+                "owner1": "serde-rs",
+                "name1": "serde",
+            })
+        );
+    }
+
+    #[test]
+    fn test_batch_results() {
+        let res: BatchResponse = serde_json::from_value(serde_json::json!({
+            "data": {
+                "r0": null,
+                "r1": {
+                    "nameWithOwner": "serde-rs/serde",
+                    "url": "https://github.com/serde-rs/serde",
+                    "description": "Serialization framework for Rust",
+                    "homepageUrl": "https://serde.rs/",
+                    "stargazerCount": 8000,
+                    "forkCount": 700,
+                    "isArchived": false,
+                    "isFork": false,
+                    "pushedAt": "2023-10-01T00:00:00Z",
+                    "primaryLanguage": { "name": "Rust" },
+                    "licenseInfo": null,
+                    "openIssues": { "totalCount": 300 },
+                    "defaultBranchRef": null,
+                    "repositoryTopics": { "nodes": [] },
+                },
+            },
+            "errors": [{
+                "type": "NOT_FOUND",
+                "path": ["r0"],
+                "message": "Could not resolve to a Repository with the name 'nobody/nothing'.",
+            }],
+        }))
+        .unwrap();
+
+        let projects = batch_results(res, 2).unwrap();
+        assert!(projects[0].is_none());
+        assert_eq!(
+            projects[1].as_ref().unwrap().name_with_owner,
+            "serde-rs/serde"
+        );
+
+        let res: BatchResponse = serde_json::from_value(serde_json::json!({
+            "data": null,
+            "errors": [{ "message": "Bad credentials" }],
+        }))
+        .unwrap();
+        assert!(batch_results(res, 2).is_err());
+    }
+}
@@ -1,5 +1,4 @@
-use super::{DateTime, URI};
-use anyhow::{Context, Result};
+use super::{DateTime, GithubError, Result, URI};
 use graphql_client::GraphQLQuery;
 use std::borrow::Cow;
 
@@ -13,37 +12,62 @@ pub struct RepoFileWithHistory;
 
 impl repo_file_with_history::ResponseData {
     pub fn file_content(&self) -> Result<Cow<str>> {
-        let blob = match self
+        use repo_file_with_history::RepoFileWithHistoryRepositoryFile as File;
+
+        let file = self
             .repository
             .as_ref()
-            .context("missing file repository")?
+            .ok_or(GithubError::MissingRepository)?
             .file
             .as_ref()
-            .context("missing file")?
-        {
-            repo_file_with_history::RepoFileWithHistoryRepositoryFile::Blob(blob) => {
-                blob.text.as_deref().clone()
-            }
-            _ => unreachable!(),
-        }
-        .unwrap_or_default();
+            .ok_or(GithubError::MissingField("file"))?;
 
-        Ok(Cow::Borrowed(blob))
+        match file {
+            File::Blob(blob) => blob
+                .text
+                .as_deref()
+                .map(Cow::Borrowed)
+                .ok_or(GithubError::BinaryFile),
+            File::Commit => Err(GithubError::UnexpectedObject {
+                expected: "Blob",
+                found: "Commit",
+            }),
+            File::Tree => Err(GithubError::UnexpectedObject {
+                expected: "Blob",
+                found: "Tree",
+            }),
+            File::Tag => Err(GithubError::UnexpectedObject {
+                expected: "Blob",
+                found: "Tag",
+            }),
+        }
     }
 
     pub fn latest_commit_date(&self) -> Result<&chrono::DateTime<chrono::Utc>> {
+        use repo_file_with_history::RepoFileWithHistoryRepositoryHistory as History;
+
+        // HEAD doesn't resolve to anything in a repository without commits
         match self
             .repository
             .as_ref()
-            .context("missing file repository")?
+            .ok_or(GithubError::MissingRepository)?
             .history
             .as_ref()
-            .context("missing file history")?
+            .ok_or(GithubError::EmptyRepository)?
         {
-            repo_file_with_history::RepoFileWithHistoryRepositoryHistory::Commit(commit) => {
-                Ok(&commit.committed_date)
-            }
-            _ => unreachable!(),
+            History::Commit(commit) => Ok(&commit.committed_date),
+            History::Blob => Err(GithubError::UnexpectedObject {
+                expected: "Commit",
+                found: "Blob",
+            }),
+            History::Tag => Err(GithubError::UnexpectedObject {
+                expected: "Commit",
+                found: "Tag",
+            }),
+            History::Tree => Err(GithubError::UnexpectedObject {
+                expected: "Commit",
+                found: "Tree",
+            }),
         }
     }
 }
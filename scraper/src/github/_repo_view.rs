@@ -1,5 +1,4 @@
-use super::{DateTime, URI};
-use anyhow::{anyhow, Context, Result};
+use super::{DateTime, GithubError, Result, URI};
 use graphql_client::GraphQLQuery;
 
 #[derive(GraphQLQuery)]
@@ -10,21 +9,200 @@ use graphql_client::GraphQLQuery;
 )]
 pub struct RepoView;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "../schemas/github.graphql",
+    query_path = "src/github/_repo_view.graphql",
+    response_derives = "Debug,Serialize,Deserialize,Clone"
+)]
+pub struct SymlinkTarget;
+
+/// Git file mode of a symbolic link
+const SYMLINK_MODE: i64 = 0o120000;
+pub const MAX_SYMLINK_DEPTH: usize = 8;
+
+/// Where the README is, as far as the root listing tells. Symlink targets
+/// aren't part of the listing, only symlinks need another request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadmeLookup {
+    Found(String),
+    /// Fetch the symlink's target and pass it to `follow_symlink`
+    Symlink(String),
+}
+
 impl repo_view::RepoViewRepositoryRootFiles {
-    pub fn find_readme_path(&self) -> Result<Option<String>> {
-        let tree = match self {
-            repo_view::RepoViewRepositoryRootFiles::Tree(tree) => tree,
-            _ => unreachable!("root files should be a tree"),
-        };
-
-        Ok(tree
-            .entries
-            .as_ref()
-            .context("missing file entries")?
+    fn entries(&self) -> Result<&[repo_view::RepoViewRepositoryRootFilesOnTreeEntries]> {
+        match self {
+            repo_view::RepoViewRepositoryRootFiles::Tree(tree) => tree
+                .entries
+                .as_deref()
+                .ok_or(GithubError::MissingField("file entries")),
+            _ => Err(GithubError::UnexpectedObject {
+                expected: "Tree",
+                found: "non-tree object",
+            }),
+        }
+    }
+
+    pub fn find_readme(&self) -> Result<ReadmeLookup> {
+        let entry = self
+            .entries()?
             .iter()
-            .find_map(|f| match f.name.to_lowercase().as_str() {
-                "readme.md" => Some(f.name.clone()),
-                _ => None,
-            }))
+            .find(|f| f.name.to_lowercase() == "readme.md")
+            .ok_or(GithubError::MissingReadme)?;
+        lookup(entry)
+    }
+
+    /// Continues the lookup at the target of the symlink `link`.
+    pub fn follow_symlink(&self, link: &str, target: Option<&str>) -> Result<ReadmeLookup> {
+        let target = target
+            .and_then(resolve_symlink)
+            .ok_or_else(|| GithubError::BrokenSymlink(link.to_string()))?;
+
+        match self.entries()?.iter().find(|f| f.name == target) {
+            Some(next) => lookup(next),
+            // we only have the root listing, anything nested we have to take on faith
+            None if target.contains('/') => Ok(ReadmeLookup::Found(target)),
+            None => Err(GithubError::BrokenSymlink(link.to_string())),
+        }
+    }
+}
+
+fn lookup(entry: &repo_view::RepoViewRepositoryRootFilesOnTreeEntries) -> Result<ReadmeLookup> {
+    match entry.type_.as_str() {
+        "tree" => Err(GithubError::ReadmeIsDirectory(entry.name.clone())),
+        "commit" => Err(GithubError::Submodule(entry.name.clone())),
+        _ if entry.mode == SYMLINK_MODE => Ok(ReadmeLookup::Symlink(entry.name.clone())),
+        _ => Ok(ReadmeLookup::Found(entry.name.clone())),
+    }
+}
+
+impl symlink_target::ResponseData {
+    /// The path a symlink points to, `None` unless it's a text blob.
+    pub fn target(&self) -> Option<&str> {
+        use symlink_target::SymlinkTargetRepositoryObject as Object;
+
+        match self.repository.as_ref()?.object.as_ref()? {
+            Object::Blob(blob) => blob.text.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Normalizes a symlink target relative to the repository root, rejecting
+/// anything that escapes it.
+fn resolve_symlink(target: &str) -> Option<String> {
+    let target = target.trim();
+    if target.starts_with('/') {
+        return None;
+    }
+
+    let mut parts = Vec::new();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root_files(entries: serde_json::Value) -> repo_view::RepoViewRepositoryRootFiles {
+        serde_json::from_value(serde_json::json!({ "__typename": "Tree", "entries": entries }))
+            .unwrap()
+    }
+
+    fn entry(name: &str, type_: &str, mode: i64) -> serde_json::Value {
+        serde_json::json!({ "name": name, "size": 0, "type": type_, "mode": mode })
+    }
+
+    #[test]
+    fn test_find_readme() {
+        let files = root_files(serde_json::json!([
+            entry("docs", "tree", 0o040000),
+            entry("Readme.md", "blob", 0o100644),
+        ]));
+        assert_eq!(
+            files.find_readme().unwrap(),
+            ReadmeLookup::Found("Readme.md".to_string())
+        );
+
+        let files = root_files(serde_json::json!([entry("README.md", "tree", 0o040000)]));
+        assert!(matches!(
+            files.find_readme(),
+            Err(GithubError::ReadmeIsDirectory(_))
+        ));
+
+        let files = root_files(serde_json::json!([entry("LICENSE", "blob", 0o100644)]));
+        assert!(matches!(
+            files.find_readme(),
+            Err(GithubError::MissingReadme)
+        ));
+    }
+
+    #[test]
+    fn test_follow_symlink() {
+        let files = root_files(serde_json::json!([
+            entry("README.md", "blob", SYMLINK_MODE),
+            entry("readme.rst.md", "blob", SYMLINK_MODE),
+            entry("list.md", "blob", 0o100644),
+        ]));
+        assert_eq!(
+            files.find_readme().unwrap(),
+            ReadmeLookup::Symlink("README.md".to_string())
+        );
+        assert_eq!(
+            files
+                .follow_symlink("README.md", Some("./list.md\n"))
+                .unwrap(),
+            ReadmeLookup::Found("list.md".to_string())
+        );
+        // chains need another round
+        assert_eq!(
+            files
+                .follow_symlink("README.md", Some("readme.rst.md"))
+                .unwrap(),
+            ReadmeLookup::Symlink("readme.rst.md".to_string())
+        );
+        assert_eq!(
+            files
+                .follow_symlink("README.md", Some("docs/README.md"))
+                .unwrap(),
+            ReadmeLookup::Found("docs/README.md".to_string())
+        );
+        assert!(matches!(
+            files.follow_symlink("README.md", Some("missing.md")),
+            Err(GithubError::BrokenSymlink(_))
+        ));
+        assert!(matches!(
+            files.follow_symlink("README.md", None),
+            Err(GithubError::BrokenSymlink(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_symlink() {
+        assert_eq!(resolve_symlink("docs/README.md").unwrap(), "docs/README.md");
+        assert_eq!(
+            resolve_symlink("./docs//README.md\n").unwrap(),
+            "docs/README.md"
+        );
+        assert_eq!(resolve_symlink("docs/../README.md").unwrap(), "README.md");
+        assert!(resolve_symlink("../README.md").is_none());
+        assert!(resolve_symlink("/etc/passwd").is_none());
+        assert!(resolve_symlink(" /etc/passwd").is_none());
+        assert!(resolve_symlink(".").is_none());
     }
 }
@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GithubError {
+    #[error("missing {0} in response")]
+    MissingField(&'static str),
+    #[error("repository not found")]
+    MissingRepository,
+    #[error("repository is empty")]
+    EmptyRepository,
+    #[error("no readme in repository root")]
+    MissingReadme,
+    #[error("readme `{0}` is a directory")]
+    ReadmeIsDirectory(String),
+    #[error("`{0}` is a submodule")]
+    Submodule(String),
+    #[error("file is binary")]
+    BinaryFile,
+    #[error("symlink `{0}` could not be resolved")]
+    BrokenSymlink(String),
+    #[error("expected a {expected} but found a {found}")]
+    UnexpectedObject {
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, GithubError>;
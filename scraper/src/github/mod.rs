@@ -3,8 +3,31 @@ pub type URI = String;
 
 pub type DateTime = chrono::DateTime<chrono::Utc>;
 
+pub const GRAPHQL_URL: &str = "https://api.github.com/graphql";
+
+mod _project_view;
 mod _repo_file;
 mod _repo_view;
+mod err;
+mod redirects;
 
+pub use _project_view::{fetch_projects, project_view};
 pub use _repo_file::{repo_file_with_history, RepoFileWithHistory};
-pub use _repo_view::{repo_view, RepoView};
+pub use _repo_view::{
+    repo_view, symlink_target, ReadmeLookup, RepoView, SymlinkTarget, MAX_SYMLINK_DEPTH,
+};
+pub use err::{GithubError, Result};
+pub use redirects::{resolve_redirect, GITHUB_URL};
+
+pub fn client(token: &str) -> anyhow::Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .user_agent("graphql-rust/0.9.0")
+        .default_headers(
+            std::iter::once((
+                reqwest::header::AUTHORIZATION,
+                reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token))?,
+            ))
+            .collect(),
+        )
+        .build()?)
+}
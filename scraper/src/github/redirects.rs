@@ -0,0 +1,89 @@
+use reqwest::{redirect::Policy, Client, StatusCode};
+use url::Url;
+
+lazy_static! {
+    pub static ref GITHUB_URL: Url = Url::parse("https://github.com").unwrap();
+}
+
+/// GraphQL doesn't follow renames or transfers, but the website still
+/// redirects the old location so we ask it where the repository went.
+pub async fn resolve_redirect(
+    base_url: &Url,
+    owner: &str,
+    repo: &str,
+) -> anyhow::Result<Option<(String, String)>> {
+    let client = Client::builder().redirect(Policy::none()).build()?;
+    let url = base_url.join(&f!("{owner}/{repo}"))?;
+    let res = client.head(url.clone()).send().await?;
+
+    if !matches!(
+        res.status(),
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::PERMANENT_REDIRECT
+    ) {
+        return Ok(None);
+    }
+
+    let Some(location) = res
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|l| l.to_str().ok())
+    else {
+        return Ok(None);
+    };
+    let target = url.join(location)?;
+    let mut path = target.path_segments().into_iter().flatten();
+
+    match (path.next(), path.next()) {
+        (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => Ok(Some((
+            owner.to_string(),
+            repo.trim_end_matches(".git").to_string(),
+        ))),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Stands in for github.com, answering every request with the given status
+    /// and location.
+    fn serve(status: &'static str, location: &'static str) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or_default() > 2 {
+                    line.clear();
+                }
+                let _ = write!(
+                    &stream,
+                    "HTTP/1.1 {status}\r\nLocation: {location}\r\nContent-Length: 0\r\n\r\n"
+                );
+            }
+        });
+        Url::parse(&f!("http://{addr}/")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_redirect() {
+        let base = serve("301 Moved Permanently", "/new-owner/new-repo");
+        let resolved = resolve_redirect(&base, "old-owner", "old-repo")
+            .await
+            .unwrap();
+        assert_eq!(
+            resolved,
+            Some((String::from("new-owner"), String::from("new-repo")))
+        );
+
+        let base = serve("404 Not Found", "");
+        let resolved = resolve_redirect(&base, "old-owner", "old-repo")
+            .await
+            .unwrap();
+        assert_eq!(resolved, None);
+    }
+}
@@ -11,8 +11,12 @@ extern crate lazy_static;
 
 mod awesome_links;
 mod commands;
+mod embeddings;
+mod events;
 mod github;
 mod parser;
+mod snapshots;
+mod store;
 
 use anyhow::Result;
 use clap::Parser;
@@ -33,7 +37,14 @@ async fn main() -> Result<()> {
 
     match app.command {
         commands::Command::Crawl(cmd) => cmd.run().await?,
+        commands::Command::Db(cmd) => cmd.run().await?,
         commands::Command::Ingest(cmd) => cmd.run().await?,
+        commands::Command::Enrich(cmd) => cmd.run().await?,
+        commands::Command::Embed(cmd) => cmd.run().await?,
+        commands::Command::Search(cmd) => cmd.run().await?,
+        commands::Command::Equivalents(cmd) => cmd.run().await?,
+        commands::Command::Snapshots(cmd) => cmd.run().await?,
+        commands::Command::Export(cmd) => cmd.run().await?,
         commands::Command::Lance(cmd) => cmd.run().await?,
     };
 
@@ -0,0 +1,71 @@
+use crate::github::{project_view, repo_view};
+
+/// Snapshots older than the given age are thinned out to one per bucket,
+/// keeping the most recent snapshot of every bucket.
+pub const RETENTION_RULES: &[(i64, Bucket)] =
+    &[(7, Bucket::Day), (90, Bucket::Week), (730, Bucket::Month)];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bucket {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RepoMetrics {
+    pub stars: i64,
+    pub forks: i64,
+    pub open_issues: i64,
+    pub commits: Option<i64>,
+}
+
+impl From<&repo_view::RepoViewRepository> for RepoMetrics {
+    fn from(repo: &repo_view::RepoViewRepository) -> Self {
+        use repo_view::RepoViewRepositoryDefaultBranchRefTarget as Target;
+
+        RepoMetrics {
+            stars: repo.stargazer_count,
+            forks: repo.fork_count,
+            open_issues: repo.open_issues.total_count,
+            commits: repo
+                .default_branch_ref
+                .as_ref()
+                .and_then(|r| r.target.as_ref())
+                .and_then(|t| match t {
+                    Target::Commit(commit) => Some(commit.history.total_count),
+                    _ => None,
+                }),
+        }
+    }
+}
+
+impl From<&project_view::ProjectDetails> for RepoMetrics {
+    fn from(project: &project_view::ProjectDetails) -> Self {
+        use project_view::ProjectDetailsDefaultBranchRefTarget as Target;
+
+        RepoMetrics {
+            stars: project.stargazer_count,
+            forks: project.fork_count,
+            open_issues: project.open_issues.total_count,
+            commits: project
+                .default_branch_ref
+                .as_ref()
+                .and_then(|r| r.target.as_ref())
+                .and_then(|t| match t {
+                    Target::Commit(commit) => Some(commit.history.total_count),
+                    _ => None,
+                }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Growth {
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub to: chrono::DateTime<chrono::Utc>,
+    pub stars: i64,
+    pub forks: i64,
+    pub open_issues: i64,
+    pub commits: Option<i64>,
+}
@@ -0,0 +1,240 @@
+use crate::events::LinkState;
+use crate::snapshots::{Growth, RepoMetrics};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+
+mod postgres;
+mod sqlite;
+
+pub use postgres::PgStore;
+pub use sqlite::SqliteStore;
+
+#[derive(Debug, PartialEq)]
+pub enum MigrationStatus {
+    Applied,
+    Pending,
+    /// The migration was applied but its file has since been edited
+    Modified,
+}
+
+#[derive(Debug)]
+pub struct Migration {
+    pub version: i64,
+    pub description: String,
+    pub status: MigrationStatus,
+}
+
+/// An awesome list as it is stored, without its links.
+#[derive(Debug, Clone)]
+pub struct ListRecord {
+    pub url: String,
+    pub owner: String,
+    pub repo: String,
+    pub description: Option<String>,
+    pub crawled_at: chrono::DateTime<chrono::Utc>,
+    pub latest_commit_at: chrono::DateTime<chrono::Utc>,
+    pub homepage_url: Option<String>,
+    pub topics: Vec<String>,
+    pub default_branch: Option<String>,
+    pub readme_path: Option<String>,
+    pub parent_url: Option<String>,
+    pub metrics: Option<RepoMetrics>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct IngestedLinks {
+    pub inserted: u64,
+    pub updated: u64,
+    pub removed: u64,
+}
+
+/// A project linked from a list, with the details fetched from its forge.
+#[derive(Debug, Clone)]
+pub struct ProjectRecord {
+    pub name_with_owner: String,
+    pub url: String,
+    pub description: Option<String>,
+    pub homepage_url: Option<String>,
+    pub primary_language: Option<String>,
+    pub license: Option<String>,
+    pub topics: Vec<String>,
+    pub is_archived: bool,
+    pub is_fork: bool,
+    pub pushed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub metrics: RepoMetrics,
+}
+
+/// A link that is still in its list, with the hash of the text its stored
+/// embedding was computed from.
+#[derive(Debug, Clone)]
+pub struct EmbeddableLink {
+    pub id: String,
+    pub link: LinkState,
+    pub content_hash: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinkEmbedding {
+    pub link_id: String,
+    pub content_hash: String,
+    pub embedding: Vec<f32>,
+}
+
+/// Everything the scraper reads from and writes to a database.
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn migrate(&self) -> Result<()>;
+
+    /// The status of every embedded migration. Only reads, a database that
+    /// was never migrated has all of them pending.
+    async fn migrations(&self) -> Result<Vec<Migration>>;
+
+    /// Records the migrations up to `version` as applied without running
+    /// them, for databases created from `schemas/db.sql` before there were
+    /// migrations. Returns how many were newly recorded.
+    async fn baseline(&self, version: i64) -> Result<u64>;
+
+    /// Drops all data and migrates from scratch.
+    async fn reset(&self) -> Result<()>;
+
+    /// Replaces the stored state of a list with a fresh crawl of it. Links
+    /// that disappeared are marked as removed and every change is recorded
+    /// as a link event.
+    async fn ingest_list(&self, list: &ListRecord, links: &[LinkState]) -> Result<IngestedLinks>;
+
+    /// `(list url, link url)` of the links added to a list on or after
+    /// `since`. Links a list had on its first crawl were never added as far as
+    /// we know, they aren't included.
+    async fn added_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<HashSet<(String, String)>>;
+
+    /// All distinct GitHub URLs linked from any list.
+    async fn linked_github_urls(&self) -> Result<Vec<String>>;
+
+    /// Lowercased `owner/repo` names mapped to the canonical name.
+    async fn repo_aliases(&self) -> Result<HashMap<String, String>>;
+
+    async fn record_alias(&self, alias: &str, name_with_owner: &str) -> Result<()>;
+
+    /// Lowercased names of the projects enriched after `since`.
+    async fn enriched_since(&self, since: chrono::DateTime<chrono::Utc>)
+        -> Result<HashSet<String>>;
+
+    /// Upserts a project and appends a snapshot of its metrics.
+    async fn store_project(&self, project: &ProjectRecord) -> Result<()>;
+
+    /// Applies the [`crate::snapshots::RETENTION_RULES`], returning the number of snapshots removed.
+    async fn downsample(&self) -> Result<u64>;
+
+    /// Compares the latest snapshot of a repository with the oldest one
+    /// inside the window.
+    async fn growth(
+        &self,
+        name_with_owner: &str,
+        window: chrono::Duration,
+    ) -> Result<Option<Growth>>;
+
+    /// Current links, with the content hash of their embedding by `model`.
+    async fn embeddable_links(&self, model: &str) -> Result<Vec<EmbeddableLink>>;
+
+    /// Upserts embeddings, replacing any computed by another model.
+    async fn store_embeddings(&self, model: &str, embeddings: &[LinkEmbedding]) -> Result<()>;
+
+    /// Makes sure every embedded migration has been applied, so we never
+    /// write into a schema that doesn't match our queries.
+    async fn ensure_migrated(&self) -> Result<()> {
+        let outdated = self
+            .migrations()
+            .await?
+            .into_iter()
+            .filter(|m| m.status != MigrationStatus::Applied)
+            .count();
+        if outdated > 0 {
+            bail!(
+                "database schema is {outdated} migration(s) behind, run `scraper db migrate` first"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Opens a store for the given URL, `sqlite:` URLs use a local file and
+/// anything else is treated as Postgres. See [`SqliteStore`] for what
+/// SQLite can't do.
+pub async fn connect(database_url: &str, max_connections: u32) -> Result<Box<dyn Store>> {
+    if database_url.starts_with("sqlite:") {
+        Ok(Box::new(SqliteStore::connect(database_url).await?))
+    } else {
+        Ok(Box::new(
+            PgStore::connect(database_url, max_connections).await?,
+        ))
+    }
+}
+
+/// Like [`connect`], but refuses to hand out a store whose schema is behind.
+pub async fn connect_migrated(database_url: &str, max_connections: u32) -> Result<Box<dyn Store>> {
+    let store = connect(database_url, max_connections).await?;
+    store.ensure_migrated().await?;
+    Ok(store)
+}
+
+fn migration_status(
+    migrator: &sqlx::migrate::Migrator,
+    applied: Vec<sqlx::migrate::AppliedMigration>,
+) -> Vec<Migration> {
+    let applied: HashMap<_, _> = applied
+        .into_iter()
+        .map(|m| (m.version, m.checksum))
+        .collect();
+
+    migrator
+        .iter()
+        .map(|m| Migration {
+            version: m.version,
+            description: m.description.to_string(),
+            status: match applied.get(&m.version) {
+                Some(checksum) if *checksum == m.checksum => MigrationStatus::Applied,
+                Some(_) => MigrationStatus::Modified,
+                None => MigrationStatus::Pending,
+            },
+        })
+        .collect()
+}
+
+/// Difference between two optional metrics, `None` if either side is missing.
+fn delta(from: Option<i64>, to: Option<i64>) -> Option<i64> {
+    Some(to? - from?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::migrate::AppliedMigration;
+
+    #[test]
+    fn test_migration_status() {
+        let mut embedded = sqlite::MIGRATOR.iter();
+        let (first, second) = (embedded.next().unwrap(), embedded.next().unwrap());
+        let applied = vec![
+            AppliedMigration {
+                version: first.version,
+                checksum: first.checksum.clone(),
+            },
+            AppliedMigration {
+                version: second.version,
+                checksum: vec![0; 48].into(),
+            },
+        ];
+
+        let statuses = migration_status(&sqlite::MIGRATOR, applied)
+            .into_iter()
+            .map(|m| m.status)
+            .collect::<Vec<_>>();
+        assert_eq!(statuses[0], MigrationStatus::Applied);
+        assert_eq!(statuses[1], MigrationStatus::Modified);
+        assert!(statuses[2..].iter().all(|s| *s == MigrationStatus::Pending));
+    }
+}
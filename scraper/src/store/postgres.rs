@@ -0,0 +1,702 @@
+use super::{
+    EmbeddableLink, IngestedLinks, LinkEmbedding, ListRecord, Migration, ProjectRecord, Store,
+};
+use crate::events::{diff_links, LinkState};
+use crate::snapshots::{Bucket, Growth, RepoMetrics, RETENTION_RULES};
+use anyhow::Result;
+use async_trait::async_trait;
+use itertools::Itertools;
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    postgres::PgPoolOptions,
+    PgExecutor, PgPool,
+};
+use std::collections::{HashMap, HashSet};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("migrations/postgres");
+
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    pub async fn connect(database_url: &str, max_connections: u32) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(database_url)
+            .await?;
+        Ok(Self { pool })
+    }
+
+    /// For the queries that only exist on Postgres, like search.
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl Store for PgStore {
+    async fn migrate(&self) -> Result<()> {
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn migrations(&self) -> Result<Vec<Migration>> {
+        let mut conn = self.pool.acquire().await?;
+        let tracked: bool =
+            sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&mut *conn)
+                .await?;
+        let applied = if tracked {
+            conn.list_applied_migrations().await?
+        } else {
+            Vec::new()
+        };
+        Ok(super::migration_status(&MIGRATOR, applied))
+    }
+
+    async fn baseline(&self, version: i64) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        tx.ensure_migrations_table().await?;
+
+        let mut recorded = 0;
+        for migration in MIGRATOR.iter().filter(|m| m.version <= version) {
+            recorded += sqlx::query(
+                r"--sql
+                INSERT INTO _sqlx_migrations(version, description, success, checksum, execution_time)
+                    VALUES ($1, $2, TRUE, $3, -1)
+                ON CONFLICT (version)
+                    DO NOTHING
+                ",
+            )
+            .bind(migration.version)
+            .bind(&*migration.description)
+            .bind(&*migration.checksum)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(recorded)
+    }
+
+    async fn reset(&self) -> Result<()> {
+        sqlx::query("DROP SCHEMA public CASCADE")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("CREATE SCHEMA public")
+            .execute(&self.pool)
+            .await?;
+        self.migrate().await
+    }
+
+    async fn ingest_list(&self, list: &ListRecord, links: &[LinkState]) -> Result<IngestedLinks> {
+        let urls = links.iter().map(|l| l.url.clone()).collect_vec();
+
+        let mut tx = self.pool.begin().await?;
+
+        let crawled = sqlx::query_scalar!(
+            r#"--sql
+            SELECT
+                EXISTS (
+                    SELECT
+                    FROM
+                        awesome_lists
+                    WHERE
+                        url = $1) AS "crawled!"
+            "#,
+            list.url
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let previous_links = sqlx::query!(
+            r"--sql
+            SELECT
+                l.url,
+                l.title,
+                l.description,
+                l.breadcrumbs,
+                l.link_type,
+                l.source_line
+            FROM
+                awesome_links l
+                JOIN awesome_lists al ON al.id = l.awesome_list_id
+            WHERE
+                al.url = $1
+                AND l.removed_at IS NULL
+        ",
+            list.url
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|l| LinkState {
+            url: l.url,
+            title: l.title.unwrap_or_default(),
+            description: l.description,
+            breadcrumbs: l.breadcrumbs,
+            link_type: l.link_type,
+            source_line: l.source_line,
+        })
+        .collect_vec();
+        let link_events = diff_links(crawled.then_some(&previous_links), links);
+
+        let awesome_list = sqlx::query!(
+            r#"--sql
+            WITH awesome_list AS (
+                INSERT INTO awesome_lists (url,
+                    crawled_at,
+                    latest_commit_at,
+                    owner,
+                    repo,
+                    description,
+                    stars,
+                    homepage_url,
+                    topics,
+                    default_branch,
+                    readme_path,
+                    link_count,
+                    parent_url)
+                    VALUES($1,
+                        $2,
+                        $3,
+                        $5,
+                        $6,
+                        $7,
+                        $8,
+                        $9,
+                        $10,
+                        $11,
+                        $12,
+                        $13,
+                        $14) ON CONFLICT (url)
+                    DO
+                    UPDATE
+                    SET
+                        crawled_at = $2,
+                        latest_commit_at = $3,
+                        owner = $5,
+                        repo = $6,
+                        description = $7,
+                        stars = $8,
+                        homepage_url = $9,
+                        topics = $10,
+                        default_branch = $11,
+                        readme_path = $12,
+                        link_count = $13,
+                        parent_url = COALESCE($14, awesome_lists.parent_url)
+                    RETURNING
+                        id
+            ),
+            link_data AS (
+                SELECT
+                    url,
+                    title,
+                    description,
+                    breadcrumbs,
+                    link_type,
+                    source_line
+                FROM
+                    json_to_recordset($4::json) AS b (url text,
+                        title text,
+                        description text,
+                        breadcrumbs text [],
+                        link_type text,
+                        source_line integer)
+            ),
+            awesome_links AS (
+                INSERT INTO awesome_links (awesome_list_id,
+                    url,
+                    title,
+                    description,
+                    breadcrumbs,
+                    link_type,
+                    source_line)
+            SELECT
+                (SELECT id FROM awesome_list),
+                url,
+                title,
+                description,
+                breadcrumbs,
+                link_type,
+                source_line
+            FROM
+                link_data ON CONFLICT (awesome_list_id,
+                    url)
+                DO
+                UPDATE
+                SET
+                    title = EXCLUDED.title,
+                    description = EXCLUDED.description,
+                    breadcrumbs = EXCLUDED.breadcrumbs,
+                    link_type = EXCLUDED.link_type,
+                    source_line = EXCLUDED.source_line,
+                    removed_at = NULL
+                -- unchanged links aren't touched, and so not counted as updated
+                WHERE (awesome_links.title,
+                    awesome_links.description,
+                    awesome_links.breadcrumbs,
+                    awesome_links.link_type,
+                    awesome_links.removed_at IS NULL)
+                    IS DISTINCT FROM (EXCLUDED.title,
+                        EXCLUDED.description,
+                        EXCLUDED.breadcrumbs,
+                        EXCLUDED.link_type,
+                        TRUE)
+                RETURNING
+                    (xmax = 0) AS inserted
+            )
+            SELECT
+                (SELECT id FROM awesome_list) AS "id!",
+                COUNT(*) FILTER (WHERE inserted) AS "inserted!",
+                COUNT(*) FILTER (WHERE NOT inserted) AS "updated!"
+            FROM
+                awesome_links;
+        "#,
+            list.url,
+            list.crawled_at,
+            list.latest_commit_at,
+            serde_json::to_value(links)?,
+            list.owner,
+            list.repo,
+            list.description,
+            list.metrics.as_ref().map(|m| m.stars as i32),
+            list.homepage_url,
+            &list.topics,
+            list.default_branch,
+            list.readme_path,
+            links.len() as i32,
+            list.parent_url
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // lines above them were added or removed, that's no change to the links
+        sqlx::query!(
+            r"--sql
+            UPDATE
+                awesome_links l
+            SET
+                source_line = b.source_line
+            FROM
+                json_to_recordset($2::json) AS b (url text,
+                    source_line integer)
+            WHERE
+                l.awesome_list_id = $1
+                AND l.url = b.url
+                AND l.source_line IS DISTINCT FROM b.source_line
+        ",
+            awesome_list.id,
+            serde_json::to_value(links)?
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // links that are no longer in the list are kept around for history
+        let removed = sqlx::query!(
+            r"--sql
+            UPDATE
+                awesome_links
+            SET
+                removed_at = $3
+            WHERE
+                awesome_list_id = $1
+                AND removed_at IS NULL
+                AND NOT (url = ANY ($2))
+        ",
+            awesome_list.id,
+            &urls,
+            list.crawled_at
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query!(
+            r"--sql
+            INSERT INTO awesome_link_events (awesome_link_id,
+                kind,
+                occurred_at,
+                old_breadcrumbs,
+                new_breadcrumbs,
+                old_description,
+                new_description)
+            SELECT
+                l.id,
+                e.kind,
+                $3,
+                e.old_breadcrumbs,
+                e.new_breadcrumbs,
+                e.old_description,
+                e.new_description
+            FROM
+                json_to_recordset($2::json) AS e (url text,
+                    kind text,
+                    old_breadcrumbs text [],
+                    new_breadcrumbs text [],
+                    old_description text,
+                    new_description text)
+                JOIN awesome_links l ON l.awesome_list_id = $1
+                    AND l.url = e.url
+        ",
+            awesome_list.id,
+            serde_json::to_value(&link_events)?,
+            list.crawled_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(metrics) = &list.metrics {
+            append_snapshot(
+                &mut *tx,
+                &f!("{list.owner}/{list.repo}"),
+                list.crawled_at,
+                metrics,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(IngestedLinks {
+            inserted: awesome_list.inserted as u64,
+            updated: awesome_list.updated as u64,
+            removed,
+        })
+    }
+
+    async fn added_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<HashSet<(String, String)>> {
+        Ok(sqlx::query!(
+            r"--sql
+            SELECT
+                al.url AS list_url,
+                l.url
+            FROM
+                awesome_link_events e
+                JOIN awesome_links l ON l.id = e.awesome_link_id
+                JOIN awesome_lists al ON al.id = l.awesome_list_id
+            WHERE
+                e.kind = 'added'
+                AND e.occurred_at >= $1
+            ",
+            since
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|r| (r.list_url, r.url))
+        .collect())
+    }
+
+    async fn linked_github_urls(&self) -> Result<Vec<String>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT DISTINCT url FROM awesome_links WHERE url LIKE 'https://github.com/%'"
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn repo_aliases(&self) -> Result<HashMap<String, String>> {
+        Ok(
+            sqlx::query!("SELECT alias, name_with_owner FROM github_repo_aliases")
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|r| (r.alias, r.name_with_owner))
+                .collect(),
+        )
+    }
+
+    async fn record_alias(&self, alias: &str, name_with_owner: &str) -> Result<()> {
+        sqlx::query!(
+            r"--sql
+            INSERT INTO github_repo_aliases (alias, name_with_owner)
+                VALUES ($1, $2)
+            ON CONFLICT (alias)
+                DO UPDATE SET
+                    name_with_owner = EXCLUDED.name_with_owner
+            ",
+            alias,
+            name_with_owner
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn enriched_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<HashSet<String>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT lower(name_with_owner) FROM github_projects WHERE enriched_at > $1",
+            since
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect())
+    }
+
+    async fn store_project(&self, project: &ProjectRecord) -> Result<()> {
+        let enriched_at = chrono::Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r"--sql
+            INSERT INTO github_projects (name_with_owner,
+                url,
+                description,
+                homepage_url,
+                stars,
+                forks,
+                primary_language,
+                license,
+                topics,
+                is_archived,
+                is_fork,
+                pushed_at,
+                enriched_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (name_with_owner)
+                DO UPDATE SET
+                    url = EXCLUDED.url,
+                    description = EXCLUDED.description,
+                    homepage_url = EXCLUDED.homepage_url,
+                    stars = EXCLUDED.stars,
+                    forks = EXCLUDED.forks,
+                    primary_language = EXCLUDED.primary_language,
+                    license = EXCLUDED.license,
+                    topics = EXCLUDED.topics,
+                    is_archived = EXCLUDED.is_archived,
+                    is_fork = EXCLUDED.is_fork,
+                    pushed_at = EXCLUDED.pushed_at,
+                    enriched_at = EXCLUDED.enriched_at
+            ",
+            project.name_with_owner,
+            project.url,
+            project.description,
+            project.homepage_url,
+            project.metrics.stars as i32,
+            project.metrics.forks as i32,
+            project.primary_language,
+            project.license,
+            &project.topics,
+            project.is_archived,
+            project.is_fork,
+            project.pushed_at,
+            enriched_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        append_snapshot(
+            &mut *tx,
+            &project.name_with_owner,
+            enriched_at,
+            &project.metrics,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn downsample(&self) -> Result<u64> {
+        let mut removed = 0;
+        for &(days, bucket) in RETENTION_RULES {
+            let bucket = match bucket {
+                Bucket::Day => "day",
+                Bucket::Week => "week",
+                Bucket::Month => "month",
+            };
+            removed += sqlx::query!(
+                r"--sql
+                DELETE FROM repo_snapshots s USING (
+                    SELECT
+                        id,
+                        row_number() OVER (PARTITION BY name_with_owner,
+                            date_trunc($2, taken_at) ORDER BY taken_at DESC) AS rank
+                    FROM
+                        repo_snapshots
+                    WHERE
+                        taken_at < $1) ranked
+                WHERE s.id = ranked.id
+                    AND ranked.rank > 1
+                ",
+                chrono::Utc::now() - chrono::Duration::days(days),
+                bucket
+            )
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        }
+
+        Ok(removed)
+    }
+
+    async fn growth(
+        &self,
+        name_with_owner: &str,
+        window: chrono::Duration,
+    ) -> Result<Option<Growth>> {
+        let snapshots = sqlx::query!(
+            r"--sql
+            (
+                SELECT taken_at, stars, forks, open_issues, commits
+                FROM repo_snapshots
+                WHERE lower(name_with_owner) = lower($1)
+                    AND taken_at >= $2
+                ORDER BY taken_at ASC
+                LIMIT 1
+            )
+            UNION ALL
+            (
+                SELECT taken_at, stars, forks, open_issues, commits
+                FROM repo_snapshots
+                WHERE lower(name_with_owner) = lower($1)
+                ORDER BY taken_at DESC
+                LIMIT 1
+            )
+            ",
+            name_with_owner,
+            chrono::Utc::now() - window
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let [from, to] = snapshots.as_slice() else {
+            return Ok(None);
+        };
+        let (Some(from_at), Some(to_at)) = (from.taken_at, to.taken_at) else {
+            return Ok(None);
+        };
+        // a single snapshot is both, and shows no growth at all
+        if from_at >= to_at {
+            return Ok(None);
+        }
+        let delta = |from: Option<i32>, to: Option<i32>| {
+            super::delta(from.map(i64::from), to.map(i64::from))
+        };
+
+        Ok(Some(Growth {
+            from: from_at,
+            to: to_at,
+            stars: delta(from.stars, to.stars).unwrap_or_default(),
+            forks: delta(from.forks, to.forks).unwrap_or_default(),
+            open_issues: delta(from.open_issues, to.open_issues).unwrap_or_default(),
+            commits: delta(from.commits, to.commits),
+        }))
+    }
+
+    async fn embeddable_links(&self, model: &str) -> Result<Vec<EmbeddableLink>> {
+        Ok(sqlx::query!(
+            r#"--sql
+            SELECT
+                l.id,
+                l.url,
+                l.title,
+                l.description,
+                l.breadcrumbs,
+                l.link_type,
+                l.source_line,
+                e.content_hash AS "content_hash?"
+            FROM
+                awesome_links l
+                LEFT JOIN link_embeddings e ON e.awesome_link_id = l.id
+                    AND e.model = $1
+            WHERE
+                l.removed_at IS NULL
+            "#,
+            model
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|l| EmbeddableLink {
+            id: l.id.to_string(),
+            link: LinkState {
+                url: l.url,
+                title: l.title.unwrap_or_default(),
+                description: l.description,
+                breadcrumbs: l.breadcrumbs,
+                link_type: l.link_type,
+                source_line: l.source_line,
+            },
+            content_hash: l.content_hash,
+        })
+        .collect())
+    }
+
+    async fn store_embeddings(&self, model: &str, embeddings: &[LinkEmbedding]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for e in embeddings {
+            sqlx::query!(
+                r"--sql
+                INSERT INTO link_embeddings (awesome_link_id,
+                    model,
+                    content_hash,
+                    embedding,
+                    embedded_at)
+                    VALUES ($1, $2, $3, $4, now())
+                ON CONFLICT (awesome_link_id)
+                    DO UPDATE SET
+                        model = EXCLUDED.model,
+                        content_hash = EXCLUDED.content_hash,
+                        embedding = EXCLUDED.embedding,
+                        embedded_at = EXCLUDED.embedded_at
+                ",
+                uuid::Uuid::parse_str(&e.link_id)?,
+                model,
+                e.content_hash,
+                &e.embedding
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+/// Appends a snapshot, ignoring it if we already have one taken at the exact
+/// same time (e.g. when the same crawl is ingested twice).
+async fn append_snapshot(
+    executor: impl PgExecutor<'_>,
+    name_with_owner: &str,
+    taken_at: chrono::DateTime<chrono::Utc>,
+    metrics: &RepoMetrics,
+) -> Result<()> {
+    sqlx::query!(
+        r"--sql
+        INSERT INTO repo_snapshots (name_with_owner,
+            taken_at,
+            stars,
+            forks,
+            open_issues,
+            commits)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (name_with_owner,
+            taken_at)
+            DO NOTHING
+        ",
+        name_with_owner,
+        taken_at,
+        metrics.stars as i32,
+        metrics.forks as i32,
+        metrics.open_issues as i32,
+        metrics.commits.map(|c| c as i32)
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,989 @@
+use super::{
+    EmbeddableLink, IngestedLinks, LinkEmbedding, ListRecord, Migration, ProjectRecord, Store,
+};
+use crate::events::{diff_links, LinkState};
+use crate::snapshots::{Bucket, Growth, RepoMetrics, RETENTION_RULES};
+use anyhow::Result;
+use async_trait::async_trait;
+use itertools::Itertools;
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    types::Json,
+    SqliteConnection, SqlitePool,
+};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+/// A single-file database for local development and tests. Queries are
+/// checked at runtime, since the `query!` macros only know about Postgres.
+///
+/// It covers ingesting, enriching, snapshots and embeddings. Search and
+/// equivalents are built on Postgres full-text search and only run there.
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    /// SQLite only allows one writer at a time, so rather than having
+    /// parallel ingests fail with "database is locked" they share a single
+    /// connection, and `ingest --concurrency` makes no difference.
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn migrate(&self) -> Result<()> {
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn migrations(&self) -> Result<Vec<Migration>> {
+        let mut conn = self.pool.acquire().await?;
+        let tracked: bool = sqlx::query_scalar(
+            r"--sql
+            SELECT count(*) > 0 FROM sqlite_master
+            WHERE type = 'table' AND name = '_sqlx_migrations'
+            ",
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let applied = if tracked {
+            conn.list_applied_migrations().await?
+        } else {
+            Vec::new()
+        };
+        Ok(super::migration_status(&MIGRATOR, applied))
+    }
+
+    async fn baseline(&self, version: i64) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        tx.ensure_migrations_table().await?;
+
+        let mut recorded = 0;
+        for migration in MIGRATOR.iter().filter(|m| m.version <= version) {
+            recorded += sqlx::query(
+                r"--sql
+                INSERT INTO _sqlx_migrations(version, description, success, checksum, execution_time)
+                    VALUES (?1, ?2, TRUE, ?3, -1)
+                ON CONFLICT (version)
+                    DO NOTHING
+                ",
+            )
+            .bind(migration.version)
+            .bind(&*migration.description)
+            .bind(&*migration.checksum)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(recorded)
+    }
+
+    async fn reset(&self) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        let objects: Vec<(String, String)> = sqlx::query_as(
+            r"--sql
+            SELECT type, name FROM sqlite_master
+            WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'
+            ",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await?;
+        for (kind, name) in objects {
+            sqlx::query(&f!("DROP {kind} IF EXISTS \"{name}\""))
+                .execute(&mut *conn)
+                .await?;
+        }
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await?;
+        drop(conn);
+
+        self.migrate().await
+    }
+
+    async fn ingest_list(&self, list: &ListRecord, links: &[LinkState]) -> Result<IngestedLinks> {
+        let urls = links.iter().map(|l| l.url.as_str()).collect_vec();
+
+        let mut tx = self.pool.begin().await?;
+
+        let crawled: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM awesome_lists WHERE url = ?1)")
+                .bind(&list.url)
+                .fetch_one(&mut *tx)
+                .await?;
+        let previous_links: Vec<LinkRow> = sqlx::query_as(
+            r"--sql
+            SELECT
+                l.url,
+                l.title,
+                l.description,
+                l.breadcrumbs,
+                l.link_type,
+                l.source_line
+            FROM
+                awesome_links l
+                JOIN awesome_lists al ON al.id = l.awesome_list_id
+            WHERE
+                al.url = ?1
+                AND l.removed_at IS NULL
+            ",
+        )
+        .bind(&list.url)
+        .fetch_all(&mut *tx)
+        .await?;
+        let previous_links = previous_links
+            .into_iter()
+            .map(LinkState::from)
+            .collect_vec();
+        let link_events = diff_links(crawled.then_some(&previous_links), links);
+
+        let list_id: String = sqlx::query_scalar(
+            r"--sql
+            INSERT INTO awesome_lists (url,
+                crawled_at,
+                latest_commit_at,
+                owner,
+                repo,
+                description,
+                stars,
+                homepage_url,
+                topics,
+                default_branch,
+                readme_path,
+                link_count,
+                parent_url)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT (url)
+                DO UPDATE SET
+                    crawled_at = excluded.crawled_at,
+                    latest_commit_at = excluded.latest_commit_at,
+                    owner = excluded.owner,
+                    repo = excluded.repo,
+                    description = excluded.description,
+                    stars = excluded.stars,
+                    homepage_url = excluded.homepage_url,
+                    topics = excluded.topics,
+                    default_branch = excluded.default_branch,
+                    readme_path = excluded.readme_path,
+                    link_count = excluded.link_count,
+                    parent_url = COALESCE(excluded.parent_url, awesome_lists.parent_url)
+            RETURNING
+                id
+            ",
+        )
+        .bind(&list.url)
+        .bind(list.crawled_at)
+        .bind(list.latest_commit_at)
+        .bind(&list.owner)
+        .bind(&list.repo)
+        .bind(&list.description)
+        .bind(list.metrics.as_ref().map(|m| m.stars))
+        .bind(&list.homepage_url)
+        .bind(Json(&list.topics))
+        .bind(&list.default_branch)
+        .bind(&list.readme_path)
+        .bind(links.len() as i64)
+        .bind(&list.parent_url)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // every link we have ever seen for this list, removed ones included,
+        // so re-added links count as updates like they do in Postgres
+        let known: HashSet<String> =
+            sqlx::query_scalar("SELECT url FROM awesome_links WHERE awesome_list_id = ?1")
+                .bind(&list_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+
+        let mut ingested = IngestedLinks::default();
+        for link in links {
+            let changed = sqlx::query(
+                r"--sql
+                INSERT INTO awesome_links (awesome_list_id,
+                    url,
+                    title,
+                    description,
+                    breadcrumbs,
+                    link_type,
+                    source_line)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT (awesome_list_id,
+                    url)
+                    DO UPDATE SET
+                        title = excluded.title,
+                        description = excluded.description,
+                        breadcrumbs = excluded.breadcrumbs,
+                        link_type = excluded.link_type,
+                        source_line = excluded.source_line,
+                        removed_at = NULL
+                    WHERE awesome_links.title IS NOT excluded.title
+                        OR awesome_links.description IS NOT excluded.description
+                        OR awesome_links.breadcrumbs IS NOT excluded.breadcrumbs
+                        OR awesome_links.link_type IS NOT excluded.link_type
+                        OR awesome_links.removed_at IS NOT NULL
+                ",
+            )
+            .bind(&list_id)
+            .bind(&link.url)
+            .bind(&link.title)
+            .bind(&link.description)
+            .bind(Json(&link.breadcrumbs))
+            .bind(&link.link_type)
+            .bind(link.source_line)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            if changed == 0 {
+                // lines above it were added or removed, that's no change to the link
+                sqlx::query(
+                    r"--sql
+                    UPDATE
+                        awesome_links
+                    SET
+                        source_line = ?3
+                    WHERE
+                        awesome_list_id = ?1
+                        AND url = ?2
+                        AND source_line IS NOT ?3
+                    ",
+                )
+                .bind(&list_id)
+                .bind(&link.url)
+                .bind(link.source_line)
+                .execute(&mut *tx)
+                .await?;
+            } else if known.contains(&link.url) {
+                ingested.updated += 1;
+            } else {
+                ingested.inserted += 1;
+            }
+        }
+
+        // links that are no longer in the list are kept around for history
+        ingested.removed = sqlx::query(
+            r"--sql
+            UPDATE
+                awesome_links
+            SET
+                removed_at = ?3
+            WHERE
+                awesome_list_id = ?1
+                AND removed_at IS NULL
+                AND url NOT IN (SELECT value FROM json_each(?2))
+            ",
+        )
+        .bind(&list_id)
+        .bind(Json(&urls))
+        .bind(list.crawled_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        for event in &link_events {
+            sqlx::query(
+                r"--sql
+                INSERT INTO awesome_link_events (awesome_link_id,
+                    kind,
+                    occurred_at,
+                    old_breadcrumbs,
+                    new_breadcrumbs,
+                    old_description,
+                    new_description)
+                SELECT
+                    id, ?3, ?4, ?5, ?6, ?7, ?8
+                FROM
+                    awesome_links
+                WHERE
+                    awesome_list_id = ?1
+                    AND url = ?2
+                ",
+            )
+            .bind(&list_id)
+            .bind(&event.url)
+            .bind(event.kind.as_str())
+            .bind(list.crawled_at)
+            .bind(event.old_breadcrumbs.as_ref().map(Json))
+            .bind(event.new_breadcrumbs.as_ref().map(Json))
+            .bind(&event.old_description)
+            .bind(&event.new_description)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(metrics) = &list.metrics {
+            append_snapshot(
+                &mut tx,
+                &f!("{list.owner}/{list.repo}"),
+                list.crawled_at,
+                metrics,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(ingested)
+    }
+
+    async fn added_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<HashSet<(String, String)>> {
+        Ok(sqlx::query_as(
+            r"--sql
+            SELECT
+                al.url,
+                l.url
+            FROM
+                awesome_link_events e
+                JOIN awesome_links l ON l.id = e.awesome_link_id
+                JOIN awesome_lists al ON al.id = l.awesome_list_id
+            WHERE
+                e.kind = 'added'
+                AND e.occurred_at >= ?1
+            ",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect())
+    }
+
+    async fn linked_github_urls(&self) -> Result<Vec<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT DISTINCT url FROM awesome_links WHERE url LIKE 'https://github.com/%'",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn repo_aliases(&self) -> Result<HashMap<String, String>> {
+        Ok(
+            sqlx::query_as("SELECT alias, name_with_owner FROM github_repo_aliases")
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    async fn record_alias(&self, alias: &str, name_with_owner: &str) -> Result<()> {
+        sqlx::query(
+            r"--sql
+            INSERT INTO github_repo_aliases (alias, name_with_owner)
+                VALUES (?1, ?2)
+            ON CONFLICT (alias)
+                DO UPDATE SET
+                    name_with_owner = excluded.name_with_owner
+            ",
+        )
+        .bind(alias)
+        .bind(name_with_owner)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn enriched_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<HashSet<String>> {
+        Ok(sqlx::query_scalar(
+            "SELECT lower(name_with_owner) FROM github_projects WHERE enriched_at > ?1",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect())
+    }
+
+    async fn store_project(&self, project: &ProjectRecord) -> Result<()> {
+        let enriched_at = chrono::Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r"--sql
+            INSERT INTO github_projects (name_with_owner,
+                url,
+                description,
+                homepage_url,
+                stars,
+                forks,
+                primary_language,
+                license,
+                topics,
+                is_archived,
+                is_fork,
+                pushed_at,
+                enriched_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT (name_with_owner)
+                DO UPDATE SET
+                    url = excluded.url,
+                    description = excluded.description,
+                    homepage_url = excluded.homepage_url,
+                    stars = excluded.stars,
+                    forks = excluded.forks,
+                    primary_language = excluded.primary_language,
+                    license = excluded.license,
+                    topics = excluded.topics,
+                    is_archived = excluded.is_archived,
+                    is_fork = excluded.is_fork,
+                    pushed_at = excluded.pushed_at,
+                    enriched_at = excluded.enriched_at
+            ",
+        )
+        .bind(&project.name_with_owner)
+        .bind(&project.url)
+        .bind(&project.description)
+        .bind(&project.homepage_url)
+        .bind(project.metrics.stars)
+        .bind(project.metrics.forks)
+        .bind(&project.primary_language)
+        .bind(&project.license)
+        .bind(Json(&project.topics))
+        .bind(project.is_archived)
+        .bind(project.is_fork)
+        .bind(project.pushed_at)
+        .bind(enriched_at)
+        .execute(&mut *tx)
+        .await?;
+
+        append_snapshot(
+            &mut tx,
+            &project.name_with_owner,
+            enriched_at,
+            &project.metrics,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn downsample(&self) -> Result<u64> {
+        let mut removed = 0;
+        for &(days, bucket) in RETENTION_RULES {
+            let bucket = match bucket {
+                Bucket::Day => "%Y-%m-%d",
+                Bucket::Week => "%Y-%W",
+                Bucket::Month => "%Y-%m",
+            };
+            removed += sqlx::query(
+                r"--sql
+                DELETE FROM repo_snapshots
+                WHERE id IN (
+                    SELECT id FROM (
+                        SELECT
+                            id,
+                            row_number() OVER (PARTITION BY name_with_owner,
+                                strftime(?2, taken_at) ORDER BY taken_at DESC) AS rank
+                        FROM
+                            repo_snapshots
+                        WHERE
+                            taken_at < ?1)
+                    WHERE rank > 1)
+                ",
+            )
+            .bind(chrono::Utc::now() - chrono::Duration::days(days))
+            .bind(bucket)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        }
+
+        Ok(removed)
+    }
+
+    async fn growth(
+        &self,
+        name_with_owner: &str,
+        window: chrono::Duration,
+    ) -> Result<Option<Growth>> {
+        type Snapshot = (chrono::DateTime<chrono::Utc>, i64, i64, i64, Option<i64>);
+
+        let oldest: Option<Snapshot> = sqlx::query_as(
+            r"--sql
+            SELECT taken_at, stars, forks, open_issues, commits
+            FROM repo_snapshots
+            WHERE lower(name_with_owner) = lower(?1)
+                AND taken_at >= ?2
+            ORDER BY taken_at ASC
+            LIMIT 1
+            ",
+        )
+        .bind(name_with_owner)
+        .bind(chrono::Utc::now() - window)
+        .fetch_optional(&self.pool)
+        .await?;
+        let latest: Option<Snapshot> = sqlx::query_as(
+            r"--sql
+            SELECT taken_at, stars, forks, open_issues, commits
+            FROM repo_snapshots
+            WHERE lower(name_with_owner) = lower(?1)
+            ORDER BY taken_at DESC
+            LIMIT 1
+            ",
+        )
+        .bind(name_with_owner)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        // a single snapshot is both, and shows no growth at all
+        let (Some(from), Some(to)) = (oldest, latest) else {
+            return Ok(None);
+        };
+        if from.0 >= to.0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Growth {
+            from: from.0,
+            to: to.0,
+            stars: to.1 - from.1,
+            forks: to.2 - from.2,
+            open_issues: to.3 - from.3,
+            commits: super::delta(from.4, to.4),
+        }))
+    }
+
+    async fn embeddable_links(&self, model: &str) -> Result<Vec<EmbeddableLink>> {
+        let links: Vec<EmbeddableLinkRow> = sqlx::query_as(
+            r"--sql
+            SELECT
+                l.id,
+                l.url,
+                l.title,
+                l.description,
+                l.breadcrumbs,
+                l.link_type,
+                l.source_line,
+                e.content_hash
+            FROM
+                awesome_links l
+                LEFT JOIN link_embeddings e ON e.awesome_link_id = l.id
+                    AND e.model = ?1
+            WHERE
+                l.removed_at IS NULL
+            ",
+        )
+        .bind(model)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(links
+            .into_iter()
+            .map(|l| EmbeddableLink {
+                id: l.id,
+                link: l.link.into(),
+                content_hash: l.content_hash,
+            })
+            .collect())
+    }
+
+    async fn store_embeddings(&self, model: &str, embeddings: &[LinkEmbedding]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for e in embeddings {
+            let blob = e
+                .embedding
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect_vec();
+            sqlx::query(
+                r"--sql
+                INSERT INTO link_embeddings (awesome_link_id,
+                    model,
+                    content_hash,
+                    embedding,
+                    embedded_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (awesome_link_id)
+                    DO UPDATE SET
+                        model = excluded.model,
+                        content_hash = excluded.content_hash,
+                        embedding = excluded.embedding,
+                        embedded_at = excluded.embedded_at
+                ",
+            )
+            .bind(&e.link_id)
+            .bind(model)
+            .bind(&e.content_hash)
+            .bind(blob)
+            .bind(chrono::Utc::now())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct LinkRow {
+    url: String,
+    title: Option<String>,
+    description: Option<String>,
+    breadcrumbs: Json<Vec<String>>,
+    link_type: Option<String>,
+    source_line: Option<i32>,
+}
+
+impl From<LinkRow> for LinkState {
+    fn from(row: LinkRow) -> Self {
+        LinkState {
+            url: row.url,
+            title: row.title.unwrap_or_default(),
+            description: row.description,
+            breadcrumbs: row.breadcrumbs.0,
+            link_type: row.link_type,
+            source_line: row.source_line,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct EmbeddableLinkRow {
+    id: String,
+    #[sqlx(flatten)]
+    link: LinkRow,
+    content_hash: Option<String>,
+}
+
+/// Appends a snapshot, ignoring it if we already have one taken at the exact
+/// same time (e.g. when the same crawl is ingested twice).
+async fn append_snapshot(
+    conn: &mut SqliteConnection,
+    name_with_owner: &str,
+    taken_at: chrono::DateTime<chrono::Utc>,
+    metrics: &RepoMetrics,
+) -> Result<()> {
+    sqlx::query(
+        r"--sql
+        INSERT INTO repo_snapshots (name_with_owner,
+            taken_at,
+            stars,
+            forks,
+            open_issues,
+            commits)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT (name_with_owner,
+            taken_at)
+            DO NOTHING
+        ",
+    )
+    .bind(name_with_owner)
+    .bind(taken_at)
+    .bind(metrics.stars)
+    .bind(metrics.forks)
+    .bind(metrics.open_issues)
+    .bind(metrics.commits)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MigrationStatus;
+    use chrono::{Datelike, Duration, Utc};
+
+    async fn store() -> SqliteStore {
+        let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+        store.migrate().await.unwrap();
+        store
+    }
+
+    async fn snapshot(store: &SqliteStore, taken_at: chrono::DateTime<Utc>, stars: i64) {
+        let mut conn = store.pool.acquire().await.unwrap();
+        let metrics = RepoMetrics {
+            stars,
+            forks: 0,
+            open_issues: 0,
+            commits: None,
+        };
+        append_snapshot(&mut conn, "tokio-rs/tokio", taken_at, &metrics)
+            .await
+            .unwrap();
+    }
+
+    async fn stars(store: &SqliteStore) -> Vec<i64> {
+        sqlx::query_scalar("SELECT stars FROM repo_snapshots ORDER BY taken_at")
+            .fetch_all(&store.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_baseline() {
+        let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+        let statuses =
+            |migrations: Vec<Migration>| migrations.into_iter().map(|m| m.status).collect_vec();
+        let pending = MIGRATOR.iter().count();
+
+        // the status alone doesn't start tracking migrations
+        let migrations = store.migrations().await.unwrap();
+        assert_eq!(migrations.len(), pending);
+        assert!(statuses(migrations)
+            .iter()
+            .all(|s| *s == MigrationStatus::Pending));
+        let tracked: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM sqlite_master WHERE name = '_sqlx_migrations'",
+        )
+        .fetch_one(&store.pool)
+        .await
+        .unwrap();
+        assert_eq!(tracked, 0);
+
+        assert_eq!(store.baseline(1).await.unwrap(), 1);
+        assert_eq!(store.baseline(1).await.unwrap(), 0);
+        let migrations = statuses(store.migrations().await.unwrap());
+        assert_eq!(migrations[0], MigrationStatus::Applied);
+        assert!(migrations[1..]
+            .iter()
+            .all(|s| *s == MigrationStatus::Pending));
+    }
+
+    #[tokio::test]
+    async fn test_baseline_existing_schema() {
+        let store = SqliteStore::connect("sqlite::memory:").await.unwrap();
+        // a database set up by hand before there were migrations
+        let initial = MIGRATOR.iter().next().unwrap();
+        sqlx::Executor::execute(&store.pool, &*initial.sql)
+            .await
+            .unwrap();
+
+        store.baseline(1).await.unwrap();
+        store.migrate().await.unwrap();
+        store.ensure_migrated().await.unwrap();
+    }
+
+    fn list(crawled_at: &str) -> ListRecord {
+        let crawled_at = crawled_at.parse().unwrap();
+        ListRecord {
+            url: String::from("https://github.com/rust-unofficial/awesome-rust"),
+            owner: String::from("rust-unofficial"),
+            repo: String::from("awesome-rust"),
+            description: None,
+            crawled_at,
+            latest_commit_at: crawled_at,
+            homepage_url: None,
+            topics: Vec::new(),
+            default_branch: None,
+            readme_path: None,
+            parent_url: None,
+            metrics: None,
+        }
+    }
+
+    fn link(url: &str) -> LinkState {
+        LinkState {
+            url: url.to_string(),
+            title: url.to_string(),
+            description: None,
+            breadcrumbs: vec![String::from("Libraries")],
+            link_type: None,
+            source_line: None,
+        }
+    }
+
+    async fn events(store: &SqliteStore) -> Vec<(String, String)> {
+        sqlx::query_as(
+            r"--sql
+            SELECT l.url, e.kind
+            FROM awesome_link_events e
+                JOIN awesome_links l ON l.id = e.awesome_link_id
+            ORDER BY l.url
+            ",
+        )
+        .fetch_all(&store.pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ingest_events() {
+        let store = store().await;
+
+        // everything in a list is new on its first crawl, that's no news
+        let links = [link("https://a"), link("https://b")];
+        store
+            .ingest_list(&list("2023-10-01T00:00:00Z"), &links)
+            .await
+            .unwrap();
+        assert!(events(&store).await.is_empty());
+
+        let links = [link("https://a"), link("https://c")];
+        store
+            .ingest_list(&list("2023-10-08T00:00:00Z"), &links)
+            .await
+            .unwrap();
+        assert_eq!(
+            events(&store).await,
+            vec![
+                (String::from("https://b"), String::from("removed")),
+                (String::from("https://c"), String::from("added")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ingest_source_lines() {
+        let store = store().await;
+        let at_line = |url, line| LinkState {
+            source_line: Some(line),
+            ..link(url)
+        };
+
+        let links = [at_line("https://a", 10), at_line("https://b", 11)];
+        store
+            .ingest_list(&list("2023-10-01T00:00:00Z"), &links)
+            .await
+            .unwrap();
+
+        // a new link above the others moves them down without changing them
+        let links = [
+            at_line("https://a", 11),
+            at_line("https://b", 12),
+            at_line("https://c", 10),
+        ];
+        let ingested = store
+            .ingest_list(&list("2023-10-08T00:00:00Z"), &links)
+            .await
+            .unwrap();
+        assert_eq!(
+            ingested,
+            IngestedLinks {
+                inserted: 1,
+                updated: 0,
+                removed: 0
+            }
+        );
+
+        let lines: Vec<(String, i32)> =
+            sqlx::query_as("SELECT url, source_line FROM awesome_links ORDER BY source_line")
+                .fetch_all(&store.pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (String::from("https://c"), 10),
+                (String::from("https://a"), 11),
+                (String::from("https://b"), 12),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_added_since() {
+        let store = store().await;
+        let since = "2023-10-05T00:00:00Z".parse().unwrap();
+
+        store
+            .ingest_list(&list("2023-10-01T00:00:00Z"), &[link("https://a")])
+            .await
+            .unwrap();
+        assert!(store.added_since(since).await.unwrap().is_empty());
+
+        let links = [link("https://a"), link("https://b")];
+        store
+            .ingest_list(&list("2023-10-08T00:00:00Z"), &links)
+            .await
+            .unwrap();
+        let list_url = String::from("https://github.com/rust-unofficial/awesome-rust");
+        assert_eq!(
+            store.added_since(since).await.unwrap(),
+            HashSet::from([(list_url, String::from("https://b"))])
+        );
+        let later = "2023-10-09T00:00:00Z".parse().unwrap();
+        assert!(store.added_since(later).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_growth() {
+        let store = store().await;
+        let window = Duration::days(30);
+        assert!(store
+            .growth("tokio-rs/tokio", window)
+            .await
+            .unwrap()
+            .is_none());
+
+        snapshot(&store, Utc::now() - Duration::days(10), 100).await;
+        assert!(store
+            .growth("tokio-rs/tokio", window)
+            .await
+            .unwrap()
+            .is_none());
+
+        snapshot(&store, Utc::now() - Duration::days(1), 150).await;
+        let growth = store
+            .growth("Tokio-rs/tokio", window)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(growth.stars, 50);
+        assert_eq!(growth.commits, None);
+    }
+
+    #[tokio::test]
+    async fn test_downsample() {
+        let now = Utc::now();
+        let noon = |days: i64| {
+            (now - Duration::days(days))
+                .date_naive()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let day = noon(10);
+        let week = noon(120);
+        let week = week - Duration::days(week.weekday().num_days_from_monday().into());
+        let month = noon(800).with_day(10).unwrap();
+
+        let store = store().await;
+        // the last week keeps everything
+        snapshot(&store, now - Duration::hours(30), 1).await;
+        snapshot(&store, now - Duration::hours(26), 2).await;
+        // then one per day
+        snapshot(&store, day, 3).await;
+        snapshot(&store, day + Duration::hours(1), 4).await;
+        // one per week after 90 days
+        snapshot(&store, week, 5).await;
+        snapshot(&store, week + Duration::days(2), 6).await;
+        // and one per month after two years
+        snapshot(&store, month, 7).await;
+        snapshot(&store, month + Duration::days(10), 8).await;
+
+        assert_eq!(store.downsample().await.unwrap(), 3);
+        assert_eq!(stars(&store).await, vec![8, 6, 4, 1, 2]);
+        assert_eq!(store.downsample().await.unwrap(), 0);
+    }
+}